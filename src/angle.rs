@@ -2,9 +2,12 @@
 use num_traits::NumCast;
 use serde::{Serialize, Deserialize};
 use std::ops::{Mul, Div, Add, Sub, Neg};
+use std::fmt;
+use std::str::FromStr;
 use float_cmp::ApproxEq;
 use crate::FullFloat;
 use crate::vector::Vec2;
+use crate::parse::ParseError;
 
 /// A type for representing an angle, without needing to remember if it is
 /// denominated in Radians, Degrees, or otherwise.  Angles are NOT automatically
@@ -89,10 +92,26 @@ impl<F: FullFloat> Angle<F>
         let twopi = two * F::PI();
         let zero: F = NumCast::from(0.0_f32).unwrap();
         // Remainder within -twopi ... +twopi
-        self.0 = self.0 % twopi;
+        self.0 %= twopi;
         if self.0 < zero { self.0 += twopi; }
     }
 
+    /// The signed angle to turn from this angle to `other` the short way
+    /// around, from -PI up to (but not including) PI
+    pub fn shortest_to(&self, other: Angle<F>) -> Angle<F> {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        let twopi = two * F::PI();
+        let mut d = (other.0 - self.0 + F::PI()) % twopi;
+        if d < F::zero() { d += twopi; }
+        Angle(d - F::PI())
+    }
+
+    /// Interpolate the short way around the circle.  The result is not
+    /// normalized.
+    pub fn lerp_shortest(&self, other: Angle<F>, t: F) -> Angle<F> {
+        Angle(self.0 + self.shortest_to(other).0 * t)
+    }
+
     pub fn sin(&self) -> F {
         self.0.sin()
     }
@@ -162,7 +181,39 @@ impl<F: FullFloat> Neg for Angle<F> {
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Angle<F> {
+/// Formats in degrees with a "deg" suffix, e.g. "90deg". Precision etc. is
+/// applied to the number.
+impl<F: FullFloat + fmt::Display> fmt::Display for Angle<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.as_degrees(), f)?;
+        write!(f, "deg")
+    }
+}
+
+/// Parses a number with an optional unit suffix: "deg", "degrees" or "°";
+/// "rad" or "radians"; "cycles" or "turns".  A bare number is taken to be
+/// in radians.
+impl<F: FullFloat> FromStr for Angle<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Angle<F>, ParseError> {
+        let s = s.trim();
+        let split = s.rfind(|c: char| c.is_ascii_digit() || c == '.')
+            .map(|i| i + 1)
+            .unwrap_or(0);
+        let (number, unit) = s.split_at(split);
+        let value = F::from_str_radix(number.trim(), 10)
+            .map_err(|_| ParseError::InvalidNumber(number.trim().to_owned()))?;
+        match unit.trim() {
+            "" | "rad" | "radians" => Ok(Angle::from_radians(value)),
+            "deg" | "degrees" | "°" => Ok(Angle::from_degrees(value)),
+            "cycles" | "turns" => Ok(Angle::from_cycles(value)),
+            other => Err(ParseError::UnknownUnit(other.to_owned())),
+        }
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Angle<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -175,7 +226,6 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Angle
 mod tests {
     use super::*;
     use std::f32::consts::PI;
-    use std::f32::EPSILON;
     use crate::vector::Vec2;
 
     #[test]
@@ -204,9 +254,9 @@ mod tests {
         let h1 = Angle::from_radians(PI);
         let h2 = Angle::from_degrees(180.0);
         let h3 = Angle::from_cycles(0.5);
-        assert!(h1.approx_eq(&h2, (2.0 * EPSILON, 2)));
-        assert!(h1.approx_eq(&h3, (2.0 * EPSILON, 2)));
-        assert!(h2.approx_eq(&h3, (2.0 * EPSILON, 2)));
+        assert!(h1.approx_eq(&h2, (2.0 * f32::EPSILON, 2)));
+        assert!(h1.approx_eq(&h3, (2.0 * f32::EPSILON, 2)));
+        assert!(h2.approx_eq(&h3, (2.0 * f32::EPSILON, 2)));
     }
 
     #[test]
@@ -217,23 +267,66 @@ mod tests {
         let q4 = Vec2::new(1.0_f32, -1.0);
 
         assert!(Angle::of_vector(&q1).approx_eq(
-            &Angle::from_cycles(1.0/8.0), (2.0 * EPSILON, 2)));
+            &Angle::from_cycles(1.0/8.0), (2.0 * f32::EPSILON, 2)));
         assert!(Angle::of_vector(&q2).approx_eq(
-            &Angle::from_cycles(3.0/8.0), (2.0 * EPSILON, 2)));
+            &Angle::from_cycles(3.0/8.0), (2.0 * f32::EPSILON, 2)));
         assert!(Angle::of_vector(&q3).approx_eq(
-            &Angle::from_cycles(-3.0/8.0), (2.0 * EPSILON, 2)));
+            &Angle::from_cycles(-3.0/8.0), (2.0 * f32::EPSILON, 2)));
         assert!(Angle::of_vector(&q4).approx_eq(
-            &Angle::from_cycles(-1.0/8.0), (2.0 * EPSILON, 2)));
+            &Angle::from_cycles(-1.0/8.0), (2.0 * f32::EPSILON, 2)));
     }
 
     #[test]
     fn test_normalize() {
         let mut a1 = Angle::from_degrees(370.0_f32);
         a1.normalize();
-        assert!(a1.as_degrees().approx_eq(10.0_f32, (2.0 * EPSILON, 2)));
+        assert!(a1.as_degrees().approx_eq(10.0_f32, (2.0 * f32::EPSILON, 2)));
 
         let mut a1 = Angle::from_degrees(-370.0_f32);
         a1.normalize();
-        assert!(a1.as_degrees().approx_eq(350.0_f32, (2.0 * EPSILON, 2)));
+        assert!(a1.as_degrees().approx_eq(350.0_f32, (2.0 * f32::EPSILON, 2)));
+    }
+
+    #[test]
+    fn test_shortest() {
+        let a = Angle::from_degrees(350.0_f64);
+        let b = Angle::from_degrees(10.0_f64);
+        assert!((a.shortest_to(b).as_degrees() - 20.0).abs() < 1e-9);
+        assert!((b.shortest_to(a).as_degrees() + 20.0).abs() < 1e-9);
+        assert!((Angle::from_degrees(-720.0_f64).shortest_to(Angle::from_degrees(90.0))
+                 .as_degrees() - 90.0).abs() < 1e-9);
+        assert!((a.lerp_shortest(b, 0.75).as_degrees() - 365.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(format!("{}", Angle::from_degrees(90.0_f64)), "90deg");
+        assert_eq!(format!("{:.2}", Angle::from_cycles(0.125_f64)), "45.00deg");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let a: Angle<f32> = "90deg".parse().unwrap();
+        assert!(a.approx_eq(&Angle::from_degrees(90.0), (2.0 * f32::EPSILON, 2)));
+        let a: Angle<f32> = "1.57rad".parse().unwrap();
+        assert_eq!(a, Angle::from_radians(1.57));
+        let a: Angle<f32> = "1.57".parse().unwrap();
+        assert_eq!(a, Angle::from_radians(1.57));
+        let a: Angle<f32> = " -45 degrees ".parse().unwrap();
+        assert_eq!(a, Angle::from_degrees(-45.0));
+        let a: Angle<f32> = "30°".parse().unwrap();
+        assert_eq!(a, Angle::from_degrees(30.0));
+        let a: Angle<f32> = "0.5 turns".parse().unwrap();
+        assert_eq!(a, Angle::from_cycles(0.5));
+        let a: Angle<f32> = "1e2deg".parse().unwrap();
+        assert_eq!(a, Angle::from_degrees(100.0));
+
+        let a = Angle::from_degrees(12.5_f64);
+        assert!(format!("{}", a).parse::<Angle<f64>>().unwrap()
+                .approx_eq(&a, (2.0 * f64::EPSILON, 2)));
+
+        assert_eq!("90 furlongs".parse::<Angle<f32>>(),
+                   Err(ParseError::UnknownUnit("furlongs".to_owned())));
+        assert!("deg".parse::<Angle<f32>>().is_err());
     }
 }
@@ -0,0 +1,310 @@
+
+//! Conversion between coordinate conventions.
+//!
+//! A `CoordinateSystem` says which axes point up and forward, and whether
+//! the system is right or left handed; right is then whichever axis
+//! completes it.  For a right handed system right is `forward × up`, and
+//! for a left handed one `up × forward`.
+//!
+//! Forward is the way a model faces.  Blender and glTF, for example, agree
+//! on the model (glTF's exporter writes Blender's (x, y, z) as (x, z, -y))
+//! once Blender's forward is taken as -Y.
+
+use std::convert::TryFrom;
+use serde::{Serialize, Deserialize};
+use crate::{FullFloat, Vec3, Point3, Direction3, Mat3, Mat4, Quat, NQuat, Position,
+            MathError};
+
+/// A signed coordinate axis
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub enum Axis {
+    PosX,
+    NegX,
+    PosY,
+    NegY,
+    PosZ,
+    NegZ,
+}
+
+impl Axis {
+    pub fn to_vec3<F: FullFloat>(&self) -> Vec3<F> {
+        let (o, z) = (F::one(), F::zero());
+        match *self {
+            Axis::PosX => Vec3::new(o, z, z),
+            Axis::NegX => Vec3::new(-o, z, z),
+            Axis::PosY => Vec3::new(z, o, z),
+            Axis::NegY => Vec3::new(z, -o, z),
+            Axis::PosZ => Vec3::new(z, z, o),
+            Axis::NegZ => Vec3::new(z, z, -o),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub enum Handedness {
+    Right,
+    Left,
+}
+
+/// A coordinate convention
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+#[serde(try_from = "UncheckedCoordinateSystem")]
+pub struct CoordinateSystem {
+    up: Axis,
+    forward: Axis,
+    handedness: Handedness,
+}
+
+impl CoordinateSystem {
+    /// Blender: Z up, models facing -Y, right handed
+    pub const BLENDER: CoordinateSystem = CoordinateSystem {
+        up: Axis::PosZ, forward: Axis::NegY, handedness: Handedness::Right
+    };
+
+    /// glTF: Y up, models facing +Z, right handed
+    pub const GLTF: CoordinateSystem = CoordinateSystem {
+        up: Axis::PosY, forward: Axis::PosZ, handedness: Handedness::Right
+    };
+
+    /// Unity: Y up, Z forward, left handed
+    pub const UNITY: CoordinateSystem = CoordinateSystem {
+        up: Axis::PosY, forward: Axis::PosZ, handedness: Handedness::Left
+    };
+
+    /// Unreal: Z up, X forward, left handed
+    pub const UNREAL: CoordinateSystem = CoordinateSystem {
+        up: Axis::PosZ, forward: Axis::PosX, handedness: Handedness::Left
+    };
+}
+
+impl CoordinateSystem {
+    /// Fails with `Singular` if `up` and `forward` lie along the same axis
+    pub fn new(up: Axis, forward: Axis, handedness: Handedness)
+               -> Result<CoordinateSystem, MathError>
+    {
+        if up.to_vec3::<f32>().dot(forward.to_vec3()) != 0.0 {
+            return Err(MathError::Singular);
+        }
+        Ok(CoordinateSystem { up, forward, handedness })
+    }
+
+    pub fn up(&self) -> Axis { self.up }
+    pub fn forward(&self) -> Axis { self.forward }
+    pub fn handedness(&self) -> Handedness { self.handedness }
+}
+
+// Deserialization goes through `new()`, so a loaded system is always valid
+#[derive(Deserialize)]
+struct UncheckedCoordinateSystem {
+    up: Axis,
+    forward: Axis,
+    handedness: Handedness,
+}
+
+impl TryFrom<UncheckedCoordinateSystem> for CoordinateSystem {
+    type Error = MathError;
+
+    fn try_from(s: UncheckedCoordinateSystem) -> Result<CoordinateSystem, MathError> {
+        CoordinateSystem::new(s.up, s.forward, s.handedness)
+    }
+}
+
+impl CoordinateSystem {
+    /// The matrix whose columns are this system's right, up and forward
+    /// axes, taking (right, up, forward) components into this system.
+    /// (Right, up and forward make a left handed triple in a right handed
+    /// system, and the determinant is -1 there.)
+    pub fn basis<F: FullFloat>(&self) -> Mat3<F> {
+        let up: Vec3<F> = self.up.to_vec3();
+        let forward: Vec3<F> = self.forward.to_vec3();
+        let right = match self.handedness {
+            Handedness::Right => forward.cross(up),
+            Handedness::Left => up.cross(forward),
+        };
+        Mat3::from_cols(right, up, forward)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A change of basis from one coordinate system to another.  This is not
+/// deserializable, as its matrix and `reflects` flag could disagree;
+/// store the two `CoordinateSystem`s instead.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize)]
+pub struct CoordinateConversion<F> {
+    matrix: Mat3<F>,
+    /// Whether the change flips handedness
+    reflects: bool,
+}
+
+impl<F: FullFloat> CoordinateConversion<F> {
+    pub fn new(from: &CoordinateSystem, to: &CoordinateSystem) -> CoordinateConversion<F> {
+        // Both bases are signed permutations, so the inverse is the transpose
+        let mut from_inv: Mat3<F> = from.basis();
+        from_inv.transpose();
+        let matrix = to.basis() * from_inv;
+        CoordinateConversion {
+            matrix,
+            reflects: from.handedness != to.handedness,
+        }
+    }
+}
+
+impl<F: FullFloat> CoordinateConversion<F> {
+    /// The change-of-basis matrix.  Its determinant is -1 when the
+    /// handedness changes.
+    pub fn mat3(&self) -> Mat3<F> {
+        self.matrix
+    }
+
+    pub fn mat4(&self) -> Mat4<F> {
+        self.matrix.as_mat4()
+    }
+
+    /// The conversion back again
+    pub fn inverse(&self) -> CoordinateConversion<F> {
+        let mut m = self.matrix;
+        m.transpose();
+        CoordinateConversion { matrix: m, reflects: self.reflects }
+    }
+
+    pub fn reflects(&self) -> bool {
+        self.reflects
+    }
+}
+
+impl<F: FullFloat> CoordinateConversion<F> {
+    pub fn vector(&self, v: &Vec3<F>) -> Vec3<F> {
+        self.matrix * v
+    }
+
+    pub fn point(&self, p: &Point3<F>) -> Point3<F> {
+        Point3(self.matrix * p.0)
+    }
+
+    pub fn direction(&self, d: &Direction3<F>) -> Direction3<F> {
+        From::from(self.matrix * **d)
+    }
+
+    /// Convert a rotation.  The rotation axis is converted like a vector,
+    /// except that under a reflection it also flips: a rotation that is
+    /// counterclockwise in one system is clockwise in the mirrored one.
+    pub fn quat(&self, q: &NQuat<F>) -> NQuat<F> {
+        let q: Quat<F> = From::from(*q);
+        let v = self.matrix * q.v;
+        let v = if self.reflects { -v } else { v };
+        NQuat::new_isnormal(v, q.w)
+    }
+
+    pub fn position(&self, p: &Position<F>) -> Position<F> {
+        Position { point: self.point(&p.point), ori: self.quat(&p.ori) }
+    }
+
+    /// Convert a transform (which takes points of one space to another,
+    /// both in the `from` system) to the `to` system
+    pub fn transform(&self, m: &Mat4<F>) -> Mat4<F> {
+        let c = self.mat4();
+        let c_inv = self.inverse().mat4();
+        (c * m) * c_inv
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Vec3, Point3, Direction3, Mat3, Mat4, NQuat, Angle, Position, MathError};
+    use super::{Axis, Handedness, CoordinateSystem, CoordinateConversion};
+
+    const MARGIN: (f64, i64) = (1e-12, 8);
+
+    #[test]
+    fn test_blender_gltf() {
+        let c = CoordinateConversion::<f64>::new(&CoordinateSystem::BLENDER,
+                                                 &CoordinateSystem::GLTF);
+        assert!(!c.reflects());
+        assert!((c.mat3().determinant() - 1.0).abs() < 1e-12);
+        let p = c.point(&Point3::new(1.0, 2.0, 3.0));
+        assert!(p.approx_eq(&Point3::new(1.0, 3.0, -2.0), MARGIN));
+        let back = c.inverse().point(&p);
+        assert!(back.approx_eq(&Point3::new(1.0, 2.0, 3.0), MARGIN));
+    }
+
+    #[test]
+    fn test_deserialize() {
+        let json = serde_json::to_string(&CoordinateSystem::BLENDER).unwrap();
+        assert_eq!(serde_json::from_str::<CoordinateSystem>(&json).unwrap(),
+                   CoordinateSystem::BLENDER);
+        let bad = r#"{"up":"PosY","forward":"NegY","handedness":"Right"}"#;
+        assert!(serde_json::from_str::<CoordinateSystem>(bad).is_err());
+    }
+
+    #[test]
+    fn test_validity() {
+        assert_eq!(CoordinateSystem::new(Axis::PosY, Axis::NegY, Handedness::Right),
+                   Err(MathError::Singular));
+        let s = CoordinateSystem::new(Axis::PosY, Axis::PosZ, Handedness::Left).unwrap();
+        assert_eq!(s, CoordinateSystem::UNITY);
+        for s in [CoordinateSystem::BLENDER, CoordinateSystem::GLTF,
+                  CoordinateSystem::UNITY, CoordinateSystem::UNREAL].iter() {
+            let det = s.basis::<f64>().determinant();
+            let expected = if s.handedness() == Handedness::Right { -1.0 } else { 1.0 };
+            assert_eq!(det, expected);
+        }
+    }
+
+    // Rotating then converting must match converting then rotating, for
+    // every kind of object
+    fn check(c: &CoordinateConversion<f64>) {
+        let axis: Direction3<f64> = From::from(Vec3::new(1.0, -2.0, 0.5));
+        let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(65.0));
+        let v = Vec3::new(0.3, 1.0, -4.0);
+        assert!(c.vector(&q.rotate(v)).approx_eq(&c.quat(&q).rotate(c.vector(&v)), MARGIN));
+        let r: Mat3<f64> = From::from(q);
+        let r2: Mat3<f64> = From::from(c.quat(&q));
+        let mut ct = c.mat3();
+        ct.transpose();
+        assert!(((c.mat3() * r) * ct).approx_eq(&r2, MARGIN));
+
+        let pos = Position { point: Point3::new(5.0, 6.0, 7.0), ori: q };
+        let m = Mat4::from_mat3(r * Mat3::scale(&Vec3::new(1.0, 2.0, 3.0)), pos.point);
+        let p = Point3::new(-1.0, 0.5, 2.0);
+        let moved = m.transform_point(&p);
+        assert!(c.transform(&m).transform_point(&c.point(&p))
+                .approx_eq(&c.point(&moved), MARGIN));
+
+        let converted = c.position(&pos);
+        let world = Point3(pos.ori.rotate(p.0) + pos.point.0);
+        let world2 = Point3(converted.ori.rotate(c.point(&p).0) + converted.point.0);
+        assert!(world2.approx_eq(&c.point(&world), MARGIN));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let systems = [CoordinateSystem::BLENDER, CoordinateSystem::GLTF,
+                       CoordinateSystem::UNITY, CoordinateSystem::UNREAL];
+        for a in systems.iter() {
+            for b in systems.iter() {
+                check(&CoordinateConversion::new(a, b));
+            }
+        }
+    }
+
+    #[test]
+    fn test_reflection() {
+        let c = CoordinateConversion::<f64>::new(&CoordinateSystem::GLTF,
+                                                 &CoordinateSystem::UNITY);
+        assert!(c.reflects());
+        assert!(c.point(&Point3::new(1.0, 2.0, 3.0)).approx_eq(&Point3::new(-1.0, 2.0, 3.0), MARGIN));
+        // a quarter turn about up turns the other way when mirrored
+        let up = Direction3::new_isnormal(0.0, 1.0, 0.0);
+        let q = NQuat::from_axis_angle(&up, &Angle::from_degrees(90.0));
+        let expected = NQuat::from_axis_angle(&up, &Angle::from_degrees(-90.0));
+        assert!(c.quat(&q).approx_eq(&expected, MARGIN));
+    }
+}
@@ -0,0 +1,302 @@
+
+//! Polar, cylindrical and spherical coordinates.
+//!
+//! In 3 dimensions these follow `Direction3::from_lat_long`: +Y is up, and
+//! the azimuth is measured about +Y from +Z towards +X.
+
+use serde::{Serialize, Deserialize};
+use float_cmp::ApproxEq;
+use crate::{FullFloat, Vec2, Vec3, Point2, Point3, Direction3, Angle};
+
+// ----------------------------------------------------------------------------
+// Polar
+
+/// A 2D position as a distance from the origin and an angle from +X
+/// counterclockwise (as `Angle::of_vector`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Polar2<F> {
+    pub radius: F,
+    pub angle: Angle<F>,
+}
+
+impl<F: FullFloat> Polar2<F> {
+    pub fn new(radius: F, angle: Angle<F>) -> Polar2<F> {
+        Polar2 { radius, angle }
+    }
+}
+
+impl<F: FullFloat> Polar2<F> {
+    /// Interpolate the radius linearly and the angle the short way around
+    pub fn lerp(&self, other: &Polar2<F>, t: F) -> Polar2<F> {
+        Polar2::new(self.radius + (other.radius - self.radius) * t,
+                    self.angle.lerp_shortest(other.angle, t))
+    }
+}
+
+impl<F: FullFloat> From<Polar2<F>> for Vec2<F> {
+    fn from(p: Polar2<F>) -> Vec2<F> {
+        Vec2::new(p.radius * p.angle.cos(), p.radius * p.angle.sin())
+    }
+}
+
+impl<F: FullFloat> From<Vec2<F>> for Polar2<F> {
+    fn from(v: Vec2<F>) -> Polar2<F> {
+        Polar2::new(v.magnitude(), Angle::of_vector(&v))
+    }
+}
+
+impl<F: FullFloat> From<Polar2<F>> for Point2<F> {
+    fn from(p: Polar2<F>) -> Point2<F> {
+        Point2(From::from(p))
+    }
+}
+
+impl<F: FullFloat> From<Point2<F>> for Polar2<F> {
+    fn from(p: Point2<F>) -> Polar2<F> {
+        From::from(p.0)
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Polar2<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.radius.approx_eq(other.radius, margin)
+            && self.angle.approx_eq(&other.angle, margin)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Cylindrical
+
+/// A 3D position as a distance from the Y axis, an azimuth about it, and a
+/// height along it
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Cylindrical3<F> {
+    pub radius: F,
+    pub azimuth: Angle<F>,
+    pub height: F,
+}
+
+impl<F: FullFloat> Cylindrical3<F> {
+    pub fn new(radius: F, azimuth: Angle<F>, height: F) -> Cylindrical3<F> {
+        Cylindrical3 { radius, azimuth, height }
+    }
+}
+
+impl<F: FullFloat> Cylindrical3<F> {
+    /// Interpolate the radius and height linearly and the azimuth the short
+    /// way around
+    pub fn lerp(&self, other: &Cylindrical3<F>, t: F) -> Cylindrical3<F> {
+        Cylindrical3::new(self.radius + (other.radius - self.radius) * t,
+                          self.azimuth.lerp_shortest(other.azimuth, t),
+                          self.height + (other.height - self.height) * t)
+    }
+}
+
+impl<F: FullFloat> From<Cylindrical3<F>> for Vec3<F> {
+    fn from(c: Cylindrical3<F>) -> Vec3<F> {
+        Vec3::new(c.radius * c.azimuth.sin(), c.height, c.radius * c.azimuth.cos())
+    }
+}
+
+impl<F: FullFloat> From<Vec3<F>> for Cylindrical3<F> {
+    fn from(v: Vec3<F>) -> Cylindrical3<F> {
+        Cylindrical3::new(v.x.hypot(v.z), Angle::from_radians(v.x.atan2(v.z)), v.y)
+    }
+}
+
+impl<F: FullFloat> From<Cylindrical3<F>> for Point3<F> {
+    fn from(c: Cylindrical3<F>) -> Point3<F> {
+        Point3(From::from(c))
+    }
+}
+
+impl<F: FullFloat> From<Point3<F>> for Cylindrical3<F> {
+    fn from(p: Point3<F>) -> Cylindrical3<F> {
+        From::from(p.0)
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Cylindrical3<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.radius.approx_eq(other.radius, margin)
+            && self.azimuth.approx_eq(&other.azimuth, margin)
+            && self.height.approx_eq(other.height, margin)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Spherical
+
+/// A 3D position as a distance from the origin, an azimuth about the Y
+/// axis, and an elevation above the XZ plane (as latitude and longitude)
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Spherical3<F> {
+    pub radius: F,
+    pub azimuth: Angle<F>,
+    /// From -PI/2 (straight down) to PI/2 (straight up)
+    pub elevation: Angle<F>,
+}
+
+impl<F: FullFloat> Spherical3<F> {
+    pub fn new(radius: F, azimuth: Angle<F>, elevation: Angle<F>) -> Spherical3<F> {
+        Spherical3 { radius, azimuth, elevation }
+    }
+}
+
+impl<F: FullFloat> Spherical3<F> {
+    /// The direction from the origin
+    pub fn direction(&self) -> Direction3<F> {
+        Direction3::from_lat_long(self.elevation, self.azimuth)
+    }
+}
+
+impl<F: FullFloat> Spherical3<F> {
+    /// Interpolate the radius and elevation linearly and the azimuth the
+    /// short way around.  This is the path an orbit camera takes, which
+    /// is not a great circle.
+    pub fn lerp(&self, other: &Spherical3<F>, t: F) -> Spherical3<F> {
+        let elevation = self.elevation.as_radians()
+            + (other.elevation.as_radians() - self.elevation.as_radians()) * t;
+        Spherical3::new(self.radius + (other.radius - self.radius) * t,
+                        self.azimuth.lerp_shortest(other.azimuth, t),
+                        Angle::from_radians(elevation))
+    }
+}
+
+impl<F: FullFloat> From<Spherical3<F>> for Vec3<F> {
+    fn from(s: Spherical3<F>) -> Vec3<F> {
+        *s.direction() * s.radius
+    }
+}
+
+/// The zero vector has zero azimuth and elevation
+impl<F: FullFloat> From<Vec3<F>> for Spherical3<F> {
+    fn from(v: Vec3<F>) -> Spherical3<F> {
+        Spherical3::new(v.magnitude(),
+                        Angle::from_radians(v.x.atan2(v.z)),
+                        Angle::from_radians(v.y.atan2(v.x.hypot(v.z))))
+    }
+}
+
+impl<F: FullFloat> From<Spherical3<F>> for Point3<F> {
+    fn from(s: Spherical3<F>) -> Point3<F> {
+        Point3(From::from(s))
+    }
+}
+
+impl<F: FullFloat> From<Point3<F>> for Spherical3<F> {
+    fn from(p: Point3<F>) -> Spherical3<F> {
+        From::from(p.0)
+    }
+}
+
+impl<F: FullFloat> From<Cylindrical3<F>> for Spherical3<F> {
+    fn from(c: Cylindrical3<F>) -> Spherical3<F> {
+        Spherical3::new(c.radius.hypot(c.height), c.azimuth,
+                        Angle::from_radians(c.height.atan2(c.radius)))
+    }
+}
+
+impl<F: FullFloat> From<Spherical3<F>> for Cylindrical3<F> {
+    fn from(s: Spherical3<F>) -> Cylindrical3<F> {
+        Cylindrical3::new(s.radius * s.elevation.cos(), s.azimuth,
+                          s.radius * s.elevation.sin())
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Spherical3<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.radius.approx_eq(other.radius, margin)
+            && self.azimuth.approx_eq(&other.azimuth, margin)
+            && self.elevation.approx_eq(&other.elevation, margin)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Vec2, Vec3, Point2, Point3, Direction3, Angle};
+    use super::{Polar2, Cylindrical3, Spherical3};
+
+    const MARGIN: (f64, i64) = (1e-12, 8);
+
+    #[test]
+    fn test_polar() {
+        let p = Polar2::new(2.0_f64, Angle::from_degrees(90.0));
+        let v: Vec2<f64> = From::from(p);
+        assert!(v.approx_eq(&Vec2::new(0.0, 2.0), MARGIN));
+        let back: Polar2<f64> = From::from(Point2::new(-3.0_f64, 0.0));
+        assert!(back.approx_eq(&Polar2::new(3.0, Angle::from_degrees(180.0)), MARGIN));
+
+        // across the wrap from 170 to -170 degrees goes through 180
+        let a = Polar2::new(1.0_f64, Angle::from_degrees(170.0));
+        let b = Polar2::new(3.0_f64, Angle::from_degrees(-170.0));
+        let mid = a.lerp(&b, 0.5);
+        assert!((mid.radius - 2.0).abs() < 1e-12);
+        assert!((mid.angle.as_degrees() - 180.0).abs() < 1e-9);
+        let v: Vec2<f64> = From::from(mid);
+        assert!(v.approx_eq(&Vec2::new(-2.0, 0.0), MARGIN));
+    }
+
+    #[test]
+    fn test_cylindrical() {
+        let c = Cylindrical3::new(2.0_f64, Angle::from_degrees(90.0), 5.0);
+        let p: Point3<f64> = From::from(c);
+        assert!(p.approx_eq(&Point3::new(2.0, 5.0, 0.0), MARGIN));
+        let back: Cylindrical3<f64> = From::from(p);
+        assert!(back.approx_eq(&c, MARGIN));
+
+        let a = Cylindrical3::new(1.0_f64, Angle::from_degrees(350.0), 0.0);
+        let b = Cylindrical3::new(1.0_f64, Angle::from_degrees(30.0), 4.0);
+        let q = a.lerp(&b, 0.25);
+        assert!((q.azimuth.as_degrees() - 360.0).abs() < 1e-9);
+        assert!((q.height - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_spherical() {
+        let v = Vec3::new(1.0_f64, -2.0, 3.0);
+        let s: Spherical3<f64> = From::from(v);
+        let back: Vec3<f64> = From::from(s);
+        assert!(back.approx_eq(&v, MARGIN));
+
+        // matches latitude and longitude
+        let s = Spherical3::new(1.0_f64, Angle::from_degrees(90.0), Angle::from_degrees(0.0));
+        assert!(s.direction().approx_eq(&Direction3::new_isnormal(1.0, 0.0, 0.0), MARGIN));
+        let up: Spherical3<f64> = From::from(Vec3::new(0.0, 4.0, 0.0));
+        assert!((up.elevation.as_degrees() - 90.0).abs() < 1e-12);
+        let zero: Spherical3<f64> = From::from(Vec3::new(0.0, 0.0, 0.0));
+        assert_eq!(zero, Spherical3::new(0.0, Angle::from_radians(0.0), Angle::from_radians(0.0)));
+
+        // cylindrical and back
+        let c: Cylindrical3<f64> = From::from(v);
+        let sc: Spherical3<f64> = From::from(c);
+        assert!(sc.approx_eq(&From::from(v), MARGIN));
+        let cs: Cylindrical3<f64> = From::from(sc);
+        assert!(cs.approx_eq(&c, MARGIN));
+
+        // an orbit across the wrap keeps its radius
+        let a = Spherical3::new(10.0_f64, Angle::from_degrees(-175.0), Angle::from_degrees(20.0));
+        let b = Spherical3::new(10.0_f64, Angle::from_degrees(175.0), Angle::from_degrees(40.0));
+        let mid = a.lerp(&b, 0.5);
+        assert!((mid.azimuth.as_degrees() + 180.0).abs() < 1e-9);
+        assert!((mid.elevation.as_degrees() - 30.0).abs() < 1e-9);
+        let p: Point3<f64> = From::from(mid);
+        assert!((p.0.magnitude() - 10.0).abs() < 1e-12);
+        assert!(p.z < 0.0);
+    }
+}
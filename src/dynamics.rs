@@ -0,0 +1,429 @@
+
+//! Rigid-body kinematics: orientation integration, inertia tensors and
+//! impulses.
+//!
+//! Angular velocities are world-space vectors whose direction is the axis of
+//! rotation and whose magnitude is the rate in radians per second.  Inertia
+//! tensors are `Mat3`s about the center of mass.
+
+use num_traits::NumCast;
+use crate::{FullFloat, Vec3, Point3, Direction3, Mat3, Quat, NQuat, Angle, MathError};
+
+// ----------------------------------------------------------------------------
+// Orientation
+
+/// The time derivative of orientation `q` under angular velocity `omega`:
+/// `dq/dt = ½ ω q`
+pub fn quat_derivative<F: FullFloat>(q: &NQuat<F>, omega: &Vec3<F>) -> Quat<F> {
+    let half: F = NumCast::from(0.5_f32).unwrap();
+    let w = Quat::new(*omega, F::zero());
+    let q: Quat<F> = From::from(*q);
+    (w * q) * half
+}
+
+/// Advance orientation `q` by angular velocity `omega` over `dt` exactly,
+/// using the exponential map.  The result is normalized.
+pub fn integrate_orientation<F: FullFloat>(q: &NQuat<F>, omega: &Vec3<F>, dt: F) -> NQuat<F> {
+    let rate = omega.magnitude();
+    if rate == F::zero() {
+        return *q;
+    }
+    let axis: Direction3<F> = From::from(*omega / rate);
+    let step = NQuat::from_axis_angle(&axis, &Angle::from_radians(rate * dt));
+    step * *q
+}
+
+/// Advance orientation `q` by angular velocity `omega` over `dt` with a
+/// single explicit Euler step on the quaternion derivative, then
+/// renormalize.  Cheaper than `integrate_orientation` and accurate to first
+/// order in `dt`.
+pub fn integrate_orientation_linear<F: FullFloat>(q: &NQuat<F>, omega: &Vec3<F>, dt: F)
+                                                   -> NQuat<F>
+{
+    let q0: Quat<F> = From::from(*q);
+    From::from(q0 + quat_derivative(q, omega) * dt)
+}
+
+// ----------------------------------------------------------------------------
+// Inertia tensors
+
+/// Transform a body-space inertia tensor into world space, `R I Rᵀ`
+pub fn world_inertia<F: FullFloat>(local: &Mat3<F>, ori: &NQuat<F>) -> Mat3<F> {
+    let r: Mat3<F> = From::from(*ori);
+    let mut rt = r;
+    rt.transpose();
+    (r * local) * rt
+}
+
+/// Inertia tensor of a solid box of the given mass and half extents
+pub fn box_inertia<F: FullFloat>(mass: F, half_extents: &Vec3<F>) -> Mat3<F> {
+    let three: F = NumCast::from(3.0_f32).unwrap();
+    let k = mass / three;
+    let (x2, y2, z2) = (half_extents.x * half_extents.x,
+                        half_extents.y * half_extents.y,
+                        half_extents.z * half_extents.z);
+    diagonal(k * (y2 + z2), k * (x2 + z2), k * (x2 + y2))
+}
+
+/// Inertia tensor of a solid sphere of the given mass and radius
+pub fn sphere_inertia<F: FullFloat>(mass: F, radius: F) -> Mat3<F> {
+    let two: F = NumCast::from(2.0_f32).unwrap();
+    let five: F = NumCast::from(5.0_f32).unwrap();
+    let i = two * mass * radius * radius / five;
+    diagonal(i, i, i)
+}
+
+/// Inertia tensor of a solid capsule along the Y axis: a cylinder of the
+/// given radius and half height, capped by hemispheres.  Mass is spread
+/// uniformly by volume.
+pub fn capsule_inertia<F: FullFloat>(mass: F, radius: F, half_height: F) -> Mat3<F> {
+    let two: F = NumCast::from(2.0_f32).unwrap();
+    let three: F = NumCast::from(3.0_f32).unwrap();
+    let four: F = NumCast::from(4.0_f32).unwrap();
+    let eight: F = NumCast::from(8.0_f32).unwrap();
+    let twelve: F = NumCast::from(12.0_f32).unwrap();
+    let five: F = NumCast::from(5.0_f32).unwrap();
+    let two_fifths = two / five;
+
+    let r2 = radius * radius;
+    let h = two * half_height;
+    let cylinder_volume = F::PI() * r2 * h;
+    let sphere_volume = four / three * F::PI() * r2 * radius;
+    let mc = mass * cylinder_volume / (cylinder_volume + sphere_volume);
+    let ms = mass - mc;
+
+    let axial = mc * r2 / two + ms * two_fifths * r2;
+    // Each hemisphere's inertia about the capsule center, via the parallel
+    // axis theorem from its own center of mass (3r/8 from the flat face)
+    let transverse = mc * (r2 / four + h * h / twelve)
+        + ms * (two_fifths * r2 + h * h / four + three * h * radius / eight);
+    diagonal(transverse, axial, transverse)
+}
+
+fn diagonal<F: FullFloat>(x: F, y: F, z: F) -> Mat3<F> {
+    Mat3::new(x, F::zero(), F::zero(),
+              F::zero(), y, F::zero(),
+              F::zero(), F::zero(), z)
+}
+
+/// Mass, center of mass and inertia tensor (about the center of mass) of a
+/// solid body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MassProperties<F> {
+    pub mass: F,
+    pub center_of_mass: Point3<F>,
+    pub inertia: Mat3<F>,
+}
+
+/// Mass properties of a closed triangle mesh of uniform density.
+///
+/// Triangles must wind counter-clockwise when seen from outside.  Uses the
+/// exact polyhedral integrals from Eberly, "Polyhedral Mass Properties".
+pub fn mesh_mass_properties<F: FullFloat>(vertices: &[Point3<F>], triangles: &[[usize; 3]],
+                                          density: F) -> MassProperties<F>
+{
+    fn subexpressions<F: FullFloat>(w0: F, w1: F, w2: F) -> (F, F, F, F, F, F) {
+        let temp0 = w0 + w1;
+        let f1 = temp0 + w2;
+        let temp1 = w0 * w0;
+        let temp2 = temp1 + w1 * temp0;
+        let f2 = temp2 + w2 * f1;
+        let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+        let g0 = f2 + w0 * (f1 + w0);
+        let g1 = f2 + w1 * (f1 + w1);
+        let g2 = f2 + w2 * (f1 + w2);
+        (f1, f2, f3, g0, g1, g2)
+    }
+
+    let mut intg = [F::zero(); 10];
+    for tri in triangles.iter() {
+        let (p0, p1, p2) = (vertices[tri[0]], vertices[tri[1]], vertices[tri[2]]);
+        let n = (p1 - p0).cross(p2 - p0);
+        let (f1x, f2x, f3x, g0x, g1x, g2x) = subexpressions(p0.x, p1.x, p2.x);
+        let (_, f2y, f3y, g0y, g1y, g2y) = subexpressions(p0.y, p1.y, p2.y);
+        let (_, f2z, f3z, g0z, g1z, g2z) = subexpressions(p0.z, p1.z, p2.z);
+        intg[0] += n.x * f1x;
+        intg[1] += n.x * f2x;
+        intg[2] += n.y * f2y;
+        intg[3] += n.z * f2z;
+        intg[4] += n.x * f3x;
+        intg[5] += n.y * f3y;
+        intg[6] += n.z * f3z;
+        intg[7] += n.x * (p0.y * g0x + p1.y * g1x + p2.y * g2x);
+        intg[8] += n.y * (p0.z * g0y + p1.z * g1y + p2.z * g2y);
+        intg[9] += n.z * (p0.x * g0z + p1.x * g1z + p2.x * g2z);
+    }
+    let divisors = [6.0_f32, 24.0, 24.0, 24.0, 60.0, 60.0, 60.0, 120.0, 120.0, 120.0];
+    for (v, d) in intg.iter_mut().zip(divisors.iter()) {
+        let d: F = NumCast::from(*d).unwrap();
+        *v = *v * density / d;
+    }
+
+    let mass = intg[0];
+    let c = Vec3::new(intg[1], intg[2], intg[3]) / mass;
+    let xx = intg[5] + intg[6] - mass * (c.y * c.y + c.z * c.z);
+    let yy = intg[4] + intg[6] - mass * (c.z * c.z + c.x * c.x);
+    let zz = intg[4] + intg[5] - mass * (c.x * c.x + c.y * c.y);
+    let xy = -(intg[7] - mass * c.x * c.y);
+    let yz = -(intg[8] - mass * c.y * c.z);
+    let xz = -(intg[9] - mass * c.z * c.x);
+
+    MassProperties {
+        mass,
+        center_of_mass: Point3(c),
+        inertia: Mat3::new(xx, xy, xz,
+                           xy, yy, yz,
+                           xz, yz, zz),
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Rigid bodies
+
+/// The kinematic state of a rigid body
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RigidBody<F> {
+    /// World position of the center of mass
+    pub position: Point3<F>,
+    pub orientation: NQuat<F>,
+    pub linear_velocity: Vec3<F>,
+    /// World-space angular velocity, in radians per second
+    pub angular_velocity: Vec3<F>,
+    /// Zero for immovable bodies
+    pub inverse_mass: F,
+    /// Inverse of the body-space inertia tensor
+    pub inverse_inertia: Mat3<F>,
+}
+
+impl<F: FullFloat> RigidBody<F> {
+    /// A body at rest at the origin.  A mass of zero or infinity makes
+    /// the body static: it has zero inverse mass and inverse inertia, so
+    /// impulses do not move it, and `inertia` is ignored.  Otherwise fails
+    /// with `Negative` or `NonFinite` for a negative or NaN mass, and with
+    /// `Singular` or `NonFinite` if the inertia tensor cannot be inverted,
+    /// as for a point mass or a flat mesh.
+    pub fn try_new(mass: F, inertia: &Mat3<F>) -> Result<RigidBody<F>, MathError> {
+        if mass.is_nan() {
+            return Err(MathError::NonFinite);
+        }
+        if mass < F::zero() {
+            return Err(MathError::Negative);
+        }
+        let is_static = mass == F::zero() || mass.is_infinite();
+        Ok(RigidBody {
+            position: Point3::new(F::zero(), F::zero(), F::zero()),
+            orientation: NQuat::identity(),
+            linear_velocity: Vec3::new(F::zero(), F::zero(), F::zero()),
+            angular_velocity: Vec3::new(F::zero(), F::zero(), F::zero()),
+            inverse_mass: if is_static { F::zero() } else { F::one() / mass },
+            inverse_inertia: if is_static { Mat3::zero() } else { inertia.try_inverse()? },
+        })
+    }
+
+    /// As `try_new`, but panics where that fails
+    pub fn new(mass: F, inertia: &Mat3<F>) -> RigidBody<F> {
+        match RigidBody::try_new(mass, inertia) {
+            Ok(body) => body,
+            Err(e) => panic!("invalid rigid body: {}", e),
+        }
+    }
+}
+
+impl<F: FullFloat> RigidBody<F> {
+    /// Inverse inertia tensor in world space
+    pub fn inverse_inertia_world(&self) -> Mat3<F> {
+        world_inertia(&self.inverse_inertia, &self.orientation)
+    }
+
+    /// Velocity of the material point at world position `point`
+    pub fn velocity_at(&self, point: &Point3<F>) -> Vec3<F> {
+        self.linear_velocity + self.angular_velocity.cross(*point - self.position)
+    }
+
+    /// Apply an impulse (change in momentum) at world position `point`
+    pub fn apply_impulse(&mut self, impulse: &Vec3<F>, point: &Point3<F>) {
+        self.linear_velocity += *impulse * self.inverse_mass;
+        let torque = (*point - self.position).cross(*impulse);
+        self.angular_velocity += self.inverse_inertia_world() * torque;
+    }
+
+    /// Apply an angular impulse (change in angular momentum)
+    pub fn apply_angular_impulse(&mut self, impulse: &Vec3<F>) {
+        self.angular_velocity += self.inverse_inertia_world() * impulse;
+    }
+
+    /// Advance position and orientation by the current velocities over `dt`
+    pub fn integrate(&mut self, dt: F) {
+        self.position = self.position + self.linear_velocity * dt;
+        self.orientation = integrate_orientation(&self.orientation, &self.angular_velocity, dt);
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Vec3, Point3, Direction3, Mat3, Quat, NQuat, Angle};
+    use super::*;
+
+    const MARGIN: (f64, i64) = (1e-12, 8);
+
+    #[test]
+    fn test_integrate_exact() {
+        let q = NQuat::<f64>::identity();
+        let omega = Vec3::new(0.0, 0.0, ::std::f64::consts::FRAC_PI_2);
+        let q = integrate_orientation(&q, &omega, 1.0);
+        let v = q.rotate(Vec3::new(1.0, 0.0, 0.0));
+        assert!(v.approx_eq(&Vec3::new(0.0, 1.0, 0.0), MARGIN));
+
+        // Many small steps compose to the same rotation
+        let mut q2 = NQuat::<f64>::identity();
+        for _ in 0..1000 {
+            q2 = integrate_orientation(&q2, &omega, 0.001);
+        }
+        assert!(q2.approx_eq(&q, (1e-9, 8)));
+        let q2: Quat<f64> = From::from(q2);
+        assert!((q2.magnitude() - 1.0).abs() < 1e-12);
+
+        let still = integrate_orientation(&q, &Vec3::new(0.0, 0.0, 0.0), 1.0);
+        assert_eq!(still, q);
+    }
+
+    #[test]
+    fn test_integrate_linear() {
+        let axis: Direction3<f64> = From::from(Vec3::new(1.0, 2.0, -1.0));
+        let q0 = NQuat::from_axis_angle(&axis, &Angle::from_degrees(40.0));
+        let omega = Vec3::new(0.3, -1.2, 2.0);
+
+        let mut exact = q0;
+        let mut linear = q0;
+        for _ in 0..1000 {
+            exact = integrate_orientation(&exact, &omega, 0.001);
+            linear = integrate_orientation_linear(&linear, &omega, 0.001);
+        }
+        let linear_q: Quat<f64> = From::from(linear);
+        assert!((linear_q.magnitude() - 1.0).abs() < 1e-12);
+        assert!(linear.dot(From::from(exact)).abs() > 1.0 - 1e-6);
+    }
+
+    #[test]
+    fn test_quat_derivative() {
+        let axis: Direction3<f64> = From::from(Vec3::new(0.5, 0.1, 1.0));
+        let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(70.0));
+        let omega = Vec3::new(0.2, 0.4, -0.9);
+        let h = 1e-6;
+        let a: Quat<f64> = From::from(integrate_orientation(&q, &omega, h));
+        let b: Quat<f64> = From::from(integrate_orientation(&q, &omega, -h));
+        let numeric = (a - b) * (0.5 / h);
+        assert!(numeric.approx_eq(&quat_derivative(&q, &omega), (1e-8, 8)));
+    }
+
+    #[test]
+    fn test_world_inertia() {
+        let local = box_inertia(2.0, &Vec3::new(1.0, 2.0, 3.0));
+        let z: Direction3<f64> = From::from(Vec3::new(0.0, 0.0, 1.0));
+        let q = NQuat::from_axis_angle(&z, &Angle::from_degrees(90.0));
+        // a quarter turn about Z swaps the X and Y moments
+        let world = world_inertia(&local, &q);
+        let expected = Mat3::new(local[(1,1)], 0.0, 0.0,
+                                 0.0, local[(0,0)], 0.0,
+                                 0.0, 0.0, local[(2,2)]);
+        assert!(world.approx_eq(&expected, MARGIN));
+    }
+
+    #[test]
+    fn test_shapes() {
+        let s = sphere_inertia(5.0_f64, 2.0);
+        assert_eq!(s[(0,0)], 8.0);
+        let b = box_inertia(12.0_f64, &Vec3::new(0.5, 1.0, 1.5));
+        assert!((b[(0,0)] - 13.0).abs() < 1e-12);
+        assert!((b[(1,1)] - 10.0).abs() < 1e-12);
+        assert!((b[(2,2)] - 5.0).abs() < 1e-12);
+
+        // A capsule with no cylinder is a sphere
+        let c = capsule_inertia(5.0_f64, 2.0, 0.0);
+        assert!(c.approx_eq(&s, MARGIN));
+        // A long thin capsule approaches a rod, m L^2 / 12
+        let c = capsule_inertia(1.0_f64, 1e-4, 0.5);
+        assert!((c[(0,0)] - 1.0 / 12.0).abs() < 1e-3);
+        assert!(c[(1,1)] < 1e-8);
+    }
+
+    fn cube(offset: Vec3<f64>) -> (Vec<Point3<f64>>, Vec<[usize; 3]>) {
+        let mut v = Vec::new();
+        for i in 0..8 {
+            let p = Vec3::new((i & 1) as f64, ((i >> 1) & 1) as f64, ((i >> 2) & 1) as f64);
+            v.push(Point3(p + offset));
+        }
+        let t = vec![
+            [0, 2, 1], [1, 2, 3], // z = 0
+            [4, 5, 6], [5, 7, 6], // z = 1
+            [0, 1, 4], [1, 5, 4], // y = 0
+            [2, 6, 3], [3, 6, 7], // y = 1
+            [0, 4, 2], [2, 4, 6], // x = 0
+            [1, 3, 5], [3, 7, 5], // x = 1
+        ];
+        (v, t)
+    }
+
+    #[test]
+    fn test_mesh() {
+        let (v, t) = cube(Vec3::new(3.0, -2.0, 5.0));
+        let props = mesh_mass_properties(&v, &t, 2.0);
+        assert!((props.mass - 2.0).abs() < 1e-12);
+        assert!(props.center_of_mass.approx_eq(&Point3::new(3.5, -1.5, 5.5), (1e-12, 8)));
+        let expected = box_inertia(2.0, &Vec3::new(0.5, 0.5, 0.5));
+        assert!(props.inertia.approx_eq(&expected, (1e-12, 64)));
+    }
+
+    #[test]
+    fn test_impulses() {
+        let inertia = box_inertia(2.0, &Vec3::new(1.0, 0.5, 0.25));
+        let mut body = RigidBody::new(2.0, &inertia);
+        body.position = Point3::new(1.0, 1.0, 1.0);
+
+        // through the center of mass: no spin
+        body.apply_impulse(&Vec3::new(4.0, 0.0, 0.0), &Point3::new(1.0, 1.0, 1.0));
+        assert_eq!(body.linear_velocity, Vec3::new(2.0, 0.0, 0.0));
+        assert_eq!(body.angular_velocity, Vec3::new(0.0, 0.0, 0.0));
+
+        // off center: spin by I^-1 (r x J)
+        body.apply_impulse(&Vec3::new(0.0, 2.0, 0.0), &Point3::new(2.0, 1.0, 1.0));
+        assert_eq!(body.linear_velocity, Vec3::new(2.0, 1.0, 0.0));
+        let expected = 2.0 / inertia[(2,2)];
+        assert!(body.angular_velocity.approx_eq(&Vec3::new(0.0, 0.0, expected), MARGIN));
+        let v = body.velocity_at(&Point3::new(2.0, 1.0, 1.0));
+        assert!(v.approx_eq(&Vec3::new(2.0, 1.0 + expected, 0.0), MARGIN));
+
+        body.integrate(0.5);
+        assert!(body.position.approx_eq(&Point3::new(2.0, 1.5, 1.0), MARGIN));
+    }
+
+    #[test]
+    fn test_static_body() {
+        for &mass in &[0.0, f64::INFINITY] {
+            let mut body = RigidBody::new(mass, &Mat3::zero());
+            body.apply_impulse(&Vec3::new(4.0, 1.0, 0.0), &Point3::new(1.0, 2.0, 0.0));
+            body.apply_angular_impulse(&Vec3::new(0.0, 0.0, 3.0));
+            assert_eq!(body.linear_velocity, Vec3::new(0.0, 0.0, 0.0));
+            assert_eq!(body.angular_velocity, Vec3::new(0.0, 0.0, 0.0));
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        let inertia = box_inertia(2.0, &Vec3::new(1.0, 0.5, 0.25));
+        assert!(RigidBody::try_new(2.0, &inertia).is_ok());
+        assert_eq!(RigidBody::try_new(-2.0, &inertia), Err(MathError::Negative));
+        assert_eq!(RigidBody::try_new(f64::NEG_INFINITY, &inertia), Err(MathError::Negative));
+        assert_eq!(RigidBody::try_new(f64::NAN, &inertia), Err(MathError::NonFinite));
+        // a point mass and a zero-extent box have singular inertia
+        assert_eq!(RigidBody::try_new(2.0, &sphere_inertia(2.0, 0.0)),
+                   Err(MathError::Singular));
+        assert_eq!(RigidBody::try_new(2.0, &box_inertia(2.0, &Vec3::new(0.0, 0.0, 0.0))),
+                   Err(MathError::Singular));
+        assert_eq!(RigidBody::try_new(2.0, &(Mat3::identity() * f64::NAN)),
+                   Err(MathError::NonFinite));
+    }
+}
@@ -0,0 +1,39 @@
+use std::error::Error;
+use std::fmt;
+
+/// An error from constructing or inverting one of the math types
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MathError {
+    /// A direction or rotation was given with zero length, so it has no
+    /// meaningful direction
+    ZeroLength,
+    /// A value that must be of unit length was not
+    NotNormalized,
+    /// A matrix has no inverse
+    Singular,
+    /// An input contained a NaN or an infinity
+    NonFinite,
+    /// A quantity that cannot be negative, such as a mass, was
+    Negative,
+}
+
+impl MathError {
+    // The message for `Display`, also reported by the serde and parse paths
+    pub(crate) fn message(self) -> &'static str {
+        match self {
+            MathError::ZeroLength => "zero length",
+            MathError::NotNormalized => "not normalized",
+            MathError::Singular => "singular matrix",
+            MathError::NonFinite => "non-finite value",
+            MathError::Negative => "negative value",
+        }
+    }
+}
+
+impl fmt::Display for MathError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.message())
+    }
+}
+
+impl Error for MathError { }
@@ -0,0 +1,378 @@
+
+//! Positions on a planet: great-circle navigation on a sphere, and
+//! geodetic coordinates on an ellipsoid.
+//!
+//! Cartesian coordinates use the same frame as `Direction3::from_lat_long`:
+//! +Y through the north pole, and longitude zero along +Z, increasing
+//! towards +X.  Bearings are measured clockwise from north.
+
+use num_traits::NumCast;
+use serde::{Serialize, Deserialize};
+use float_cmp::ApproxEq;
+use crate::{FullFloat, Angle, Vec3, Point3, Direction3};
+
+/// A latitude and longitude
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct LatLong<F> {
+    pub latitude: Angle<F>,
+    pub longitude: Angle<F>,
+}
+
+impl<F: FullFloat> LatLong<F> {
+    #[inline]
+    pub fn new(latitude: Angle<F>, longitude: Angle<F>) -> LatLong<F> {
+        LatLong { latitude, longitude }
+    }
+}
+
+impl<F: FullFloat> LatLong<F> {
+    #[inline]
+    pub fn from_degrees(latitude: F, longitude: F) -> LatLong<F> {
+        LatLong::new(Angle::from_degrees(latitude), Angle::from_degrees(longitude))
+    }
+}
+
+impl<F: FullFloat> From<Direction3<F>> for LatLong<F> {
+    fn from(d: Direction3<F>) -> LatLong<F> {
+        let (lat, lon) = d.to_lat_long();
+        LatLong::new(lat, lon)
+    }
+}
+
+impl<F: FullFloat> From<LatLong<F>> for Direction3<F> {
+    fn from(ll: LatLong<F>) -> Direction3<F> {
+        Direction3::from_lat_long(ll.latitude, ll.longitude)
+    }
+}
+
+// -- spherical ---------------------------------------------------------------
+
+impl<F: FullFloat> LatLong<F> {
+    /// The point at this location on a sphere of the given radius
+    pub fn to_point(&self, radius: F) -> Point3<F> {
+        let d: Direction3<F> = From::from(*self);
+        Point3(*d * radius)
+    }
+}
+
+impl<F: FullFloat> LatLong<F> {
+    /// The location of a point (relative to the sphere's center), and its
+    /// distance from the center
+    pub fn from_point(p: &Point3<F>) -> (LatLong<F>, F) {
+        let r = p.magnitude();
+        let d: Direction3<F> = From::from(**p);
+        (From::from(d), r)
+    }
+}
+
+impl<F: FullFloat> LatLong<F> {
+    /// The angle subtended at the center of the sphere by the great circle
+    /// between two locations (haversine formula, stable for small distances)
+    pub fn central_angle(&self, other: &LatLong<F>) -> Angle<F> {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        let (phi1, phi2) = (self.latitude.as_radians(), other.latitude.as_radians());
+        let dphi = phi2 - phi1;
+        let dlambda = other.longitude.as_radians() - self.longitude.as_radians();
+        let a = (dphi / two).sin().powi(2)
+            + phi1.cos() * phi2.cos() * (dlambda / two).sin().powi(2);
+        let a = a.max(F::zero()).min(F::one());
+        Angle::from_radians(two * a.sqrt().atan2((F::one() - a).sqrt()))
+    }
+}
+
+impl<F: FullFloat> LatLong<F> {
+    /// Great-circle distance on a sphere of the given radius
+    pub fn distance(&self, other: &LatLong<F>, radius: F) -> F {
+        self.central_angle(other).as_radians() * radius
+    }
+}
+
+impl<F: FullFloat> LatLong<F> {
+    /// Bearing at which to leave `self` along the great circle to `other`,
+    /// within 0 to 2*PI
+    pub fn initial_bearing(&self, other: &LatLong<F>) -> Angle<F> {
+        let (phi1, phi2) = (self.latitude.as_radians(), other.latitude.as_radians());
+        let dlambda = other.longitude.as_radians() - self.longitude.as_radians();
+        let y = dlambda.sin() * phi2.cos();
+        let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * dlambda.cos();
+        let mut bearing = Angle::from_radians(y.atan2(x));
+        bearing.normalize();
+        bearing
+    }
+}
+
+impl<F: FullFloat> LatLong<F> {
+    /// Bearing on arrival at `other` along the great circle from `self`,
+    /// within 0 to 2*PI
+    pub fn final_bearing(&self, other: &LatLong<F>) -> Angle<F> {
+        let mut bearing = other.initial_bearing(self) + Angle::from_radians(F::PI());
+        bearing.normalize();
+        bearing
+    }
+}
+
+impl<F: FullFloat> LatLong<F> {
+    /// The location a `fraction` of the way along the great circle from
+    /// `self` to `other` (0 is `self`, 1 is `other`).
+    ///
+    /// Antipodal locations have no unique great circle; `self` is returned.
+    pub fn intermediate(&self, other: &LatLong<F>, fraction: F) -> LatLong<F> {
+        let delta = self.central_angle(other).as_radians();
+        let sin_delta = delta.sin();
+        if sin_delta.abs() <= F::epsilon() {
+            return *self;
+        }
+        let a = ((F::one() - fraction) * delta).sin() / sin_delta;
+        let b = (fraction * delta).sin() / sin_delta;
+        let d1: Direction3<F> = From::from(*self);
+        let d2: Direction3<F> = From::from(*other);
+        let v: Vec3<F> = *d1 * a + *d2 * b;
+        let d: Direction3<F> = From::from(v);
+        From::from(d)
+    }
+}
+
+impl<F: FullFloat> LatLong<F> {
+    /// The location reached by travelling `distance` along a great circle,
+    /// leaving at `bearing`, on a sphere of the given radius
+    pub fn destination(&self, bearing: Angle<F>, distance: F, radius: F) -> LatLong<F> {
+        let delta = distance / radius;
+        let (sd, cd) = delta.sin_cos();
+        let (sb, cb) = bearing.as_radians().sin_cos();
+        let (sphi1, cphi1) = self.latitude.as_radians().sin_cos();
+        let sphi2 = (sphi1 * cd + cphi1 * sd * cb).max(-F::one()).min(F::one());
+        let phi2 = sphi2.asin();
+        let dlambda = (sb * sd * cphi1).atan2(cd - sphi1 * sphi2);
+        LatLong::new(Angle::from_radians(phi2), self.longitude + Angle::from_radians(dlambda))
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &LatLong<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.latitude.approx_eq(&other.latitude, margin)
+            && self.longitude.approx_eq(&other.longitude, margin)
+    }
+}
+
+// -- ellipsoidal -------------------------------------------------------------
+
+/// An oblate ellipsoid of revolution, as used for geodetic coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Ellipsoid<F> {
+    /// Equatorial radius
+    pub semi_major: F,
+    /// (a - b) / a
+    pub flattening: F,
+}
+
+impl<F: FullFloat> Ellipsoid<F> {
+    #[inline]
+    pub fn new(semi_major: F, flattening: F) -> Ellipsoid<F> {
+        Ellipsoid { semi_major, flattening }
+    }
+}
+
+impl<F: FullFloat> Ellipsoid<F> {
+    /// The WGS84 ellipsoid, in meters
+    pub fn wgs84() -> Ellipsoid<F> {
+        let a: F = NumCast::from(6378137.0_f64).unwrap();
+        let inv_f: F = NumCast::from(298.257223563_f64).unwrap();
+        Ellipsoid::new(a, F::one() / inv_f)
+    }
+}
+
+impl<F: FullFloat> Ellipsoid<F> {
+    /// Polar radius
+    pub fn semi_minor(&self) -> F {
+        self.semi_major * (F::one() - self.flattening)
+    }
+
+    /// Square of the first eccentricity
+    pub fn eccentricity_squared(&self) -> F {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        self.flattening * (two - self.flattening)
+    }
+
+    /// Mean radius (2a + b) / 3, suitable for spherical approximations
+    pub fn mean_radius(&self) -> F {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        let three: F = NumCast::from(3.0_f32).unwrap();
+        (two * self.semi_major + self.semi_minor()) / three
+    }
+
+    // Prime vertical radius of curvature at a geodetic latitude
+    fn prime_vertical_radius(&self, sin_lat: F) -> F {
+        self.semi_major / (F::one() - self.eccentricity_squared() * sin_lat * sin_lat).sqrt()
+    }
+}
+
+impl<F: FullFloat> Ellipsoid<F> {
+    /// The point at a geodetic location and height above the ellipsoid
+    pub fn to_point(&self, location: &LatLong<F>, height: F) -> Point3<F> {
+        let (slat, clat) = location.latitude.as_radians().sin_cos();
+        let (slon, clon) = location.longitude.as_radians().sin_cos();
+        let n = self.prime_vertical_radius(slat);
+        let e2 = self.eccentricity_squared();
+        Point3::new((n + height) * clat * slon,
+                    (n * (F::one() - e2) + height) * slat,
+                    (n + height) * clat * clon)
+    }
+}
+
+impl<F: FullFloat> Ellipsoid<F> {
+    /// The geodetic location of a point, and its height above the ellipsoid
+    pub fn from_point(&self, p: &Point3<F>) -> (LatLong<F>, F) {
+        let e2 = self.eccentricity_squared();
+        let a = self.semi_major;
+        let r = (p.x * p.x + p.z * p.z).sqrt();
+        let lon = p.x.atan2(p.z);
+
+        // Fixed-point iteration on the latitude; converges to full precision
+        // within a few steps for terrestrial heights
+        let mut lat = p.y.atan2(r * (F::one() - e2));
+        for _ in 0..5 {
+            let n = self.prime_vertical_radius(lat.sin());
+            lat = (p.y + e2 * n * lat.sin()).atan2(r);
+        }
+        let (slat, clat) = lat.sin_cos();
+        let n = self.prime_vertical_radius(slat);
+        let height = r * clat + p.y * slat - a * a / n;
+        (LatLong::new(Angle::from_radians(lat), Angle::from_radians(lon)), height)
+    }
+}
+
+impl<F: FullFloat> Ellipsoid<F> {
+    /// Convert a geodetic location and height into spherical (geocentric)
+    /// coordinates: the location as seen from the center, and the radius
+    pub fn to_spherical(&self, location: &LatLong<F>, height: F) -> (LatLong<F>, F) {
+        LatLong::from_point(&self.to_point(location, height))
+    }
+}
+
+impl<F: FullFloat> Ellipsoid<F> {
+    /// Convert spherical (geocentric) coordinates into a geodetic location
+    /// and height
+    pub fn from_spherical(&self, location: &LatLong<F>, radius: F) -> (LatLong<F>, F) {
+        self.from_point(&location.to_point(radius))
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Angle, Point3};
+    use super::{LatLong, Ellipsoid};
+
+    const R: f64 = 6371e3;
+
+    fn london() -> LatLong<f64> { LatLong::from_degrees(51.5007, -0.1246) }
+    fn new_york() -> LatLong<f64> { LatLong::from_degrees(40.6892, -74.0445) }
+
+    #[test]
+    fn test_distance() {
+        // Reference values computed with the haversine formula
+        let d = london().distance(&new_york(), R);
+        assert!((d - 5574.8e3).abs() < 1e3);
+        assert_eq!(london().distance(&london(), R), 0.0);
+
+        let equator = LatLong::<f64>::from_degrees(0.0, 0.0);
+        let pole = LatLong::from_degrees(90.0, 0.0);
+        let quarter = equator.central_angle(&pole);
+        assert!(quarter.approx_eq(&Angle::from_degrees(90.0), (1e-12, 4)));
+    }
+
+    #[test]
+    fn test_bearings() {
+        let b = london().initial_bearing(&new_york());
+        assert!((b.as_degrees() - 288.34).abs() < 0.01);
+        let b = london().final_bearing(&new_york());
+        assert!((b.as_degrees() - 231.19).abs() < 0.01);
+
+        // due east along the equator
+        let a = LatLong::<f64>::from_degrees(0.0, 10.0);
+        let b = LatLong::from_degrees(0.0, 20.0);
+        assert!((a.initial_bearing(&b).as_degrees() - 90.0).abs() < 1e-9);
+        assert!((a.final_bearing(&b).as_degrees() - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_intermediate() {
+        let margin = (1e-12, 16);
+        let (a, b) = (london(), new_york());
+        assert!(a.intermediate(&b, 0.0).approx_eq(&a, margin));
+        assert!(a.intermediate(&b, 1.0).approx_eq(&b, margin));
+
+        let mid = a.intermediate(&b, 0.5);
+        let (da, db) = (a.distance(&mid, R), mid.distance(&b, R));
+        assert!((da - db).abs() < 1e-3);
+        assert!((da + db - a.distance(&b, R)).abs() < 1e-3);
+    }
+
+    #[test]
+    fn test_destination() {
+        let (a, b) = (london(), new_york());
+        let d = a.destination(a.initial_bearing(&b), a.distance(&b, R), R);
+        assert!(d.distance(&b, R) < 1e-3);
+
+        // due north a quarter of the way round reaches the pole
+        let equator = LatLong::<f64>::from_degrees(0.0, 30.0);
+        let quarter = R * ::std::f64::consts::FRAC_PI_2;
+        let d = equator.destination(Angle::from_degrees(0.0), quarter, R);
+        assert!((d.latitude.as_degrees() - 90.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_sphere_points() {
+        let p = london().to_point(R);
+        assert!((p.magnitude() - R).abs() < 1e-6);
+        let (ll, r) = LatLong::from_point(&p);
+        assert!(ll.approx_eq(&london(), (1e-12, 16)));
+        assert!((r - R).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ellipsoid() {
+        let wgs84 = Ellipsoid::<f64>::wgs84();
+        assert!((wgs84.semi_minor() - 6356752.314245).abs() < 1e-3);
+
+        // on the equator and at the poles the ellipsoid is at its radii
+        let p = wgs84.to_point(&LatLong::from_degrees(0.0, 90.0), 0.0);
+        assert!(p.approx_eq(&Point3::new(6378137.0, 0.0, 0.0), (1e-6, 4)));
+        let p = wgs84.to_point(&LatLong::from_degrees(90.0, 0.0), 100.0);
+        assert!((p.y - wgs84.semi_minor() - 100.0).abs() < 1e-6);
+
+        for &(lat, lon, h) in [(51.5, -0.12, 35.0), (-33.9, 151.2, 0.0),
+                               (89.9, 10.0, 8848.0), (-90.0, 0.0, -100.0)].iter() {
+            let ll = LatLong::from_degrees(lat, lon);
+            let (back, height) = wgs84.from_point(&wgs84.to_point(&ll, h));
+            assert!((back.latitude.as_degrees() - lat).abs() < 1e-9);
+            if lat.abs() < 90.0 {
+                assert!((back.longitude.as_degrees() - lon).abs() < 1e-9);
+            }
+            assert!((height - h).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn test_spherical_ellipsoidal() {
+        let wgs84 = Ellipsoid::<f64>::wgs84();
+        let geodetic = LatLong::from_degrees(45.0, 7.0);
+        let (geocentric, radius) = wgs84.to_spherical(&geodetic, 1000.0);
+
+        // geocentric latitude is smaller away from the equator and poles
+        assert!(geocentric.latitude.as_degrees() < 45.0);
+        assert!(geocentric.latitude.as_degrees() > 44.7);
+        assert!((geocentric.longitude.as_degrees() - 7.0).abs() < 1e-9);
+
+        let (back, h) = wgs84.from_spherical(&geocentric, radius);
+        assert!(back.approx_eq(&geodetic, (1e-12, 64)));
+        assert!((h - 1000.0).abs() < 1e-6);
+    }
+}
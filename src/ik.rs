@@ -0,0 +1,454 @@
+
+//! Inverse kinematics.
+//!
+//! `two_bone` solves a limb of two bones exactly.  Longer chains are solved
+//! iteratively by `Chain::solve_ccd` (cyclic coordinate descent) or
+//! `Chain::solve_fabrik` (forward and backward reaching).  Both solvers
+//! visit joints in a fixed order and use no randomness, so the same chain
+//! and target give bit-identical results on every machine with the same
+//! floating point behavior.
+
+use serde::{Serialize, Deserialize};
+use crate::{FullFloat, Vec3, Point3, Direction3, NQuat, Angle, Position};
+use crate::quat::perpendicular;
+
+// ----------------------------------------------------------------------------
+// Two bones
+
+/// Solve a two-bone limb (such as hip, knee and ankle) analytically.
+///
+/// `root` stays fixed and the bone lengths are taken from the current
+/// `mid` and `end`.  The limb bends in the plane through `root`, `target`
+/// and `pole`, with the middle joint on the pole's side.  A target out of
+/// reach is approached as closely as the bones allow.  Returns the new
+/// middle and end points.
+pub fn two_bone<F: FullFloat>(root: &Point3<F>, mid: &Point3<F>, end: &Point3<F>,
+                              target: &Point3<F>, pole: &Point3<F>)
+                              -> (Point3<F>, Point3<F>)
+{
+    let two = F::one() + F::one();
+    let a = (*mid - *root).magnitude();
+    let b = (*end - *mid).magnitude();
+
+    let dir = Direction3::try_from_vec(*target - *root)
+        .or_else(|_| Direction3::try_from_vec(*end - *root))
+        .unwrap_or_else(|_| Direction3::new_isnormal(F::one(), F::zero(), F::zero()));
+
+    // The part of a vector perpendicular to the reach direction
+    let across = |v: Vec3<F>| Direction3::try_from_vec(v - *dir * (*dir).dot(v));
+    let bend = across(*pole - *root)
+        .or_else(|_| across(*mid - *root))
+        .unwrap_or_else(|_| perpendicular(dir));
+
+    let d = (*target - *root).magnitude()
+        .max((a - b).abs())
+        .min(a + b);
+
+    // Law of cosines for the angle at the root
+    let cos = if d == F::zero() {
+        F::zero()
+    } else {
+        ((a * a + d * d - b * b) / (two * a * d)).max(-F::one()).min(F::one())
+    };
+    let sin = (F::one() - cos * cos).sqrt();
+
+    let new_mid = Point3(root.0 + *dir * (a * cos) + *bend * (a * sin));
+    let new_end = Point3(root.0 + *dir * d);
+    (new_mid, new_end)
+}
+
+// ----------------------------------------------------------------------------
+// Joint limits
+
+/// How far a joint may swing away from its twist axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub enum SwingLimit<F> {
+    /// No limit
+    Free,
+    /// A circular cone of the given half-angle
+    Cone(Angle<F>),
+    /// An elliptical cone, allowing up to `max_a` of rotation about `axis_a`
+    /// and up to `max_b` about `axis_b`.  Both axes are perpendicular to
+    /// each other and to the twist axis.
+    Elliptical {
+        axis_a: Direction3<F>,
+        axis_b: Direction3<F>,
+        max_a: Angle<F>,
+        max_b: Angle<F>,
+    },
+}
+
+/// A joint limit on the swing-twist decomposition of a joint's rotation
+/// relative to its parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct SwingTwistLimit<F> {
+    /// The twist axis, in the parent's frame.  This is usually the rest
+    /// direction of the bone.
+    pub axis: Direction3<F>,
+    pub swing: SwingLimit<F>,
+    pub min_twist: Angle<F>,
+    pub max_twist: Angle<F>,
+}
+
+impl<F: FullFloat> SwingTwistLimit<F> {
+    /// The closest rotation within the limit (swing and twist being
+    /// clamped separately)
+    pub fn apply(&self, q: &NQuat<F>) -> NQuat<F> {
+        let (swing, twist) = q.swing_twist(self.axis);
+        let swing = match self.swing {
+            SwingLimit::Free => swing,
+            SwingLimit::Cone(max) => swing.clamp_cone(max),
+            SwingLimit::Elliptical { axis_a, axis_b, max_a, max_b } =>
+                swing.clamp_elliptical_cone(axis_a, axis_b, max_a, max_b),
+        };
+        swing * twist.clamp_twist(self.axis, self.min_twist, self.max_twist)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Chains
+
+/// One joint of a chain, and the bone leading from it to the next joint
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Joint<F> {
+    /// Rotation relative to the parent joint (or to the chain's base)
+    pub rotation: NQuat<F>,
+    /// The bone, from this joint to the next, in this joint's frame
+    pub bone: Vec3<F>,
+    pub limit: Option<SwingTwistLimit<F>>,
+}
+
+impl<F: FullFloat> Joint<F> {
+    /// An unlimited joint at rest
+    pub fn new(bone: Vec3<F>) -> Joint<F> {
+        Joint { rotation: NQuat::identity(), bone, limit: None }
+    }
+}
+
+/// A chain of joints, each one the parent of the next.  The end of the last
+/// bone is the end effector.
+#[derive(Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Chain<F> {
+    /// The fixed position and orientation of the first joint's parent
+    pub base: Position<F>,
+    pub joints: Vec<Joint<F>>,
+}
+
+/// The outcome of an iterative solve
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct IkResult<F> {
+    /// Iterations performed
+    pub iterations: usize,
+    /// Final distance from the end effector to the target
+    pub error: F,
+    /// Whether the error came within the tolerance
+    pub reached: bool,
+}
+
+impl<F: FullFloat> Chain<F> {
+    pub fn new(base: Position<F>, joints: Vec<Joint<F>>) -> Chain<F> {
+        Chain { base, joints }
+    }
+}
+
+impl<F: FullFloat> Chain<F> {
+    /// The world orientation of each joint
+    pub fn world_rotations(&self) -> Vec<NQuat<F>> {
+        let mut parent = self.base.ori;
+        self.joints.iter().map(|j| {
+            parent = parent * j.rotation;
+            parent
+        }).collect()
+    }
+
+    /// The world position of each joint, followed by the end effector
+    pub fn positions(&self) -> Vec<Point3<F>> {
+        let mut out = Vec::with_capacity(self.joints.len() + 1);
+        let mut p = self.base.point;
+        out.push(p);
+        for (j, r) in self.joints.iter().zip(self.world_rotations()) {
+            p = Point3(p.0 + r.rotate(j.bone));
+            out.push(p);
+        }
+        out
+    }
+
+    pub fn end_effector(&self) -> Point3<F> {
+        *self.positions().last().unwrap()
+    }
+
+    /// The total length of the bones
+    pub fn length(&self) -> F {
+        self.joints.iter().fold(F::zero(), |sum, j| sum + j.bone.magnitude())
+    }
+}
+
+impl<F: FullFloat> Chain<F> {
+    // Turn joint `i` (whose parent has world orientation `parent`) by the
+    // world-space rotation `delta`, respecting its limit.  Returns the new
+    // world orientation.
+    fn turn_joint(&mut self, i: usize, parent: NQuat<F>, delta: NQuat<F>) -> NQuat<F> {
+        let joint = &mut self.joints[i];
+        let local = parent.conjugate() * delta * parent * joint.rotation;
+        joint.rotation = match joint.limit {
+            Some(ref limit) => limit.apply(&local),
+            None => local,
+        };
+        parent * joint.rotation
+    }
+
+    fn parent_rotation(&self, rotations: &[NQuat<F>], i: usize) -> NQuat<F> {
+        if i == 0 { self.base.ori } else { rotations[i - 1] }
+    }
+
+    fn result(&self, target: &Point3<F>, iterations: usize, tolerance: F) -> IkResult<F> {
+        let error = (self.end_effector() - *target).magnitude();
+        IkResult { iterations, error, reached: error <= tolerance }
+    }
+}
+
+impl<F: FullFloat> Chain<F> {
+    /// Reach for `target` by cyclic coordinate descent: each iteration
+    /// turns every joint in turn, from the last to the first, to point the
+    /// end effector at the target.  Stops once the end effector is within
+    /// `tolerance` of the target, or after `max_iterations`.
+    ///
+    /// CCD is cheap and handles joint limits naturally, but tends to curl
+    /// the joints nearest the end more than those near the base.
+    pub fn solve_ccd(&mut self, target: &Point3<F>, max_iterations: usize, tolerance: F)
+                     -> IkResult<F>
+    {
+        let n = self.joints.len();
+        for iteration in 0..max_iterations {
+            let result = self.result(target, iteration, tolerance);
+            if result.reached {
+                return result;
+            }
+            for i in (0..n).rev() {
+                let positions = self.positions();
+                let rotations = self.world_rotations();
+                let to_end = Direction3::try_from_vec(positions[n] - positions[i]);
+                let to_target = Direction3::try_from_vec(*target - positions[i]);
+                if let (Ok(to_end), Ok(to_target)) = (to_end, to_target) {
+                    let delta = NQuat::rotation_between(to_end, to_target);
+                    let parent = self.parent_rotation(&rotations, i);
+                    self.turn_joint(i, parent, delta);
+                }
+            }
+        }
+        self.result(target, max_iterations, tolerance)
+    }
+}
+
+impl<F: FullFloat> Chain<F> {
+    /// Reach for `target` by FABRIK (forward and backward reaching inverse
+    /// kinematics).  Each iteration drags the joint positions from the end
+    /// to the target and back to the base, then turns the joints (in
+    /// order, respecting their limits) to match.  Stops once the end
+    /// effector is within `tolerance` of the target, or after
+    /// `max_iterations`.
+    ///
+    /// FABRIK spreads the bend along the chain and usually needs fewer
+    /// iterations than CCD.
+    pub fn solve_fabrik(&mut self, target: &Point3<F>, max_iterations: usize, tolerance: F)
+                        -> IkResult<F>
+    {
+        let n = self.joints.len();
+        let lengths: Vec<F> = self.joints.iter().map(|j| j.bone.magnitude()).collect();
+        for iteration in 0..max_iterations {
+            let result = self.result(target, iteration, tolerance);
+            if result.reached {
+                return result;
+            }
+            let mut p = self.positions();
+
+            // Backward, from the target to the base
+            p[n] = *target;
+            for i in (0..n).rev() {
+                if let Ok(d) = Direction3::try_from_vec(p[i] - p[i + 1]) {
+                    p[i] = Point3(p[i + 1].0 + *d * lengths[i]);
+                }
+            }
+            // Forward, from the base back to the target
+            p[0] = self.base.point;
+            for i in 0..n {
+                if let Ok(d) = Direction3::try_from_vec(p[i + 1] - p[i]) {
+                    p[i + 1] = Point3(p[i].0 + *d * lengths[i]);
+                }
+            }
+
+            // Turn the joints to match.  Limits may keep a joint from
+            // reaching its new position, so each bone aims from where its
+            // joint actually ended up.
+            let mut parent = self.base.ori;
+            let mut at = self.base.point;
+            for i in 0..n {
+                let world = parent * self.joints[i].rotation;
+                let current = Direction3::try_from_vec(world.rotate(self.joints[i].bone));
+                let wanted = Direction3::try_from_vec(p[i + 1] - at);
+                parent = match (current, wanted) {
+                    (Ok(current), Ok(wanted)) => {
+                        let delta = NQuat::rotation_between(current, wanted);
+                        self.turn_joint(i, parent, delta)
+                    },
+                    _ => world,
+                };
+                at = Point3(at.0 + parent.rotate(self.joints[i].bone));
+            }
+        }
+        self.result(target, max_iterations, tolerance)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec3, Point3, Direction3, NQuat, Angle, Position};
+    use super::{two_bone, Chain, Joint, SwingLimit, SwingTwistLimit};
+
+    #[test]
+    fn test_two_bone() {
+        let root = Point3::new(0.0_f64, 0.0, 0.0);
+        let mid = Point3::new(0.0, -3.0, 0.0);
+        let end = Point3::new(0.0, -7.0, 0.0);
+        let pole = Point3::new(0.0, 0.0, 10.0);
+
+        // reachable: lengths kept, target met, knee towards the pole
+        let target = Point3::new(2.0, -4.0, 1.0);
+        let (m, e) = two_bone(&root, &mid, &end, &target, &pole);
+        assert!((e - target).magnitude() < 1e-12);
+        assert!(((m - root).magnitude() - 3.0).abs() < 1e-12);
+        assert!(((e - m).magnitude() - 4.0).abs() < 1e-12);
+        assert!(m.z > 0.0);
+        // and in the plane of root, target and pole
+        let normal = (target - root).cross(pole - root);
+        assert!((m - root).dot(normal).abs() < 1e-12);
+
+        // out of reach: straight towards the target
+        let target = Point3::new(0.0, -20.0, 0.0);
+        let (m, e) = two_bone(&root, &mid, &end, &target, &pole);
+        assert!((m.0 - Vec3::new(0.0, -3.0, 0.0)).magnitude() < 1e-12);
+        assert!((e.0 - Vec3::new(0.0, -7.0, 0.0)).magnitude() < 1e-12);
+
+        // too close: folded as far as it goes
+        let target = Point3::new(0.0, -0.5, 0.0);
+        let (m, e) = two_bone(&root, &mid, &end, &target, &pole);
+        assert!(((e - root).magnitude() - 1.0).abs() < 1e-12);
+        assert!(((e - m).magnitude() - 4.0).abs() < 1e-12);
+    }
+
+    fn arm() -> Chain<f64> {
+        let base = Position { point: Point3::new(1.0, 2.0, 3.0), ori: NQuat::identity() };
+        Chain::new(base, vec![Joint::new(Vec3::new(0.0, 2.0, 0.0)),
+                              Joint::new(Vec3::new(0.0, 1.5, 0.0)),
+                              Joint::new(Vec3::new(0.0, 1.0, 0.0)),
+                              Joint::new(Vec3::new(0.0, 0.5, 0.0))])
+    }
+
+    fn assert_lengths(chain: &Chain<f64>) {
+        let p = chain.positions();
+        for (i, j) in chain.joints.iter().enumerate() {
+            assert!(((p[i + 1] - p[i]).magnitude() - j.bone.magnitude()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_ccd() {
+        let target = Point3::new(3.0, 4.0, 2.0);
+        let mut chain = arm();
+        let result = chain.solve_ccd(&target, 100, 1e-6);
+        assert!(result.reached);
+        assert!(result.error <= 1e-6);
+        assert!((chain.end_effector() - target).magnitude() <= 1e-6);
+        assert_lengths(&chain);
+
+        // deterministic
+        let mut again = arm();
+        again.solve_ccd(&target, 100, 1e-6);
+        assert_eq!(chain, again);
+
+        // out of reach: gets as close as it can without reaching
+        let far = Point3::new(1.0, 2.0, 20.0);
+        let mut chain = arm();
+        let result = chain.solve_ccd(&far, 20, 1e-6);
+        assert!(!result.reached);
+        assert_eq!(result.iterations, 20);
+        // (CCD straightens a chain only slowly)
+        assert!((result.error - (17.0 - chain.length())).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_fabrik() {
+        let target = Point3::new(3.0, 4.0, 2.0);
+        let mut chain = arm();
+        let result = chain.solve_fabrik(&target, 100, 1e-6);
+        assert!(result.reached);
+        assert!((chain.end_effector() - target).magnitude() <= 1e-6);
+        assert_lengths(&chain);
+
+        let mut again = arm();
+        assert_eq!(again.solve_fabrik(&target, 100, 1e-6), result);
+        assert_eq!(chain, again);
+
+        // already there
+        let mut chain = arm();
+        let end = chain.end_effector();
+        assert_eq!(chain.solve_fabrik(&end, 100, 1e-6).iterations, 0);
+    }
+
+    #[test]
+    fn test_limits() {
+        let up = Direction3::new_isnormal(0.0_f64, 1.0, 0.0);
+        let limit = SwingTwistLimit {
+            axis: up,
+            swing: SwingLimit::Cone(Angle::from_degrees(30.0)),
+            min_twist: Angle::from_degrees(-10.0),
+            max_twist: Angle::from_degrees(10.0),
+        };
+        let check = |chain: &Chain<f64>| {
+            for j in chain.joints.iter() {
+                let (swing, twist) = j.rotation.swing_twist(up);
+                let (_, angle) = swing.as_axis_angle();
+                assert!(angle.as_degrees() <= 30.0 + 1e-9);
+                assert!(twist.twist_angle(up).as_degrees().abs() <= 10.0 + 1e-9);
+            }
+        };
+
+        // behind and below the base, which the limits cannot reach
+        let target = Point3::new(1.0, 0.0, 0.0);
+        for fabrik in [false, true].iter() {
+            let mut chain = arm();
+            for j in chain.joints.iter_mut() {
+                j.limit = Some(limit);
+            }
+            let result = if *fabrik {
+                chain.solve_fabrik(&target, 50, 1e-6)
+            } else {
+                chain.solve_ccd(&target, 50, 1e-6)
+            };
+            assert!(!result.reached);
+            check(&chain);
+            assert_lengths(&chain);
+        }
+
+        // and an elliptical limit on a reachable target
+        let x = Direction3::new_isnormal(1.0_f64, 0.0, 0.0);
+        let z = Direction3::new_isnormal(0.0_f64, 0.0, 1.0);
+        let mut chain = arm();
+        for j in chain.joints.iter_mut() {
+            j.limit = Some(SwingTwistLimit {
+                swing: SwingLimit::Elliptical { axis_a: x, axis_b: z,
+                                                max_a: Angle::from_degrees(45.0),
+                                                max_b: Angle::from_degrees(45.0) },
+                .. limit
+            });
+        }
+        let target = Point3::new(3.5, 5.5, 3.0);
+        assert!(chain.solve_fabrik(&target, 200, 1e-4).reached);
+    }
+}
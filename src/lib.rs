@@ -12,12 +12,61 @@ pub use self::matrix::{Mat2, Mat3, Mat4};
 pub mod quat;
 pub use self::quat::{Quat, NQuat};
 
+pub mod rotor;
+pub use self::rotor::{Bivector3, Rotor3};
+
 pub mod angle;
 pub use self::angle::Angle;
 
 pub mod position;
 pub use self::position::Position;
 
+pub mod rot2;
+pub use self::rot2::Rot2;
+
+pub mod transform2;
+pub use self::transform2::{Transform2, Affine2};
+
+pub mod plane;
+pub use self::plane::Plane;
+
+pub mod line;
+pub use self::line::Line3;
+
+pub mod projection;
+pub use self::projection::Viewport;
+
+pub mod noise;
+
+pub mod parse;
+pub use self::parse::ParseError;
+
+pub mod error;
+pub use self::error::MathError;
+
+pub mod serde_repr;
+
+pub mod world;
+pub use self::world::{WorldPosition, Sector};
+
+pub mod geodesy;
+
+pub mod coords;
+pub use self::coords::{Polar2, Cylindrical3, Spherical3};
+
+pub mod coordinate_system;
+pub use self::coordinate_system::{CoordinateSystem, CoordinateConversion};
+
+pub mod space;
+pub use self::space::{Point3In, Vec3In, Mat4From, PositionFrom};
+
+pub mod dynamics;
+
+pub mod ik;
+
+#[cfg(feature = "rand")]
+pub mod random;
+
 use num_traits::{Float, FloatConst, NumAssignOps, NumCast};
 use float_cmp::{Ulps, ApproxEq};
 
@@ -0,0 +1,270 @@
+
+//! Lines in Plücker coordinates.
+//!
+//! A line through point `p` with direction `d` is stored as the pair
+//! `(d, m)` where `m = p × d` is its moment about the origin.  This is
+//! independent of which point on the line is used, and makes tests between
+//! lines exact sign computations with no division.
+
+use serde::{Serialize, Deserialize};
+use float_cmp::ApproxEq;
+use crate::{FullFloat, Vec3, Vec4, Point3, Direction3, Mat4, Position, Plane};
+
+/// An oriented line in 3 dimensions, in Plücker coordinates
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Line3<F> {
+    /// Direction (not necessarily normalized)
+    pub direction: Vec3<F>,
+    /// Moment about the origin, `p × direction` for any point `p` on the line
+    pub moment: Vec3<F>,
+}
+
+impl<F: FullFloat> Line3<F> {
+    /// The line through `a` and `b`, directed from `a` to `b`
+    pub fn from_points(a: &Point3<F>, b: &Point3<F>) -> Line3<F> {
+        Line3 {
+            direction: *b - *a,
+            moment: a.0.cross(b.0),
+        }
+    }
+}
+
+impl<F: FullFloat> Line3<F> {
+    pub fn from_point_direction(p: &Point3<F>, d: &Direction3<F>) -> Line3<F> {
+        Line3 {
+            direction: **d,
+            moment: p.0.cross(**d),
+        }
+    }
+}
+
+impl<F: FullFloat> Line3<F> {
+    /// The point on the line closest to the origin
+    pub fn point(&self) -> Point3<F> {
+        Point3(self.direction.cross(self.moment) / self.direction.squared_magnitude())
+    }
+
+    /// The normalized direction
+    pub fn unit_direction(&self) -> Direction3<F> {
+        From::from(self.direction)
+    }
+
+    /// The same line with the opposite orientation
+    pub fn reverse(&self) -> Line3<F> {
+        Line3 { direction: -self.direction, moment: -self.moment }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Distances and closest points
+
+impl<F: FullFloat> Line3<F> {
+    pub fn distance_to_point(&self, p: &Point3<F>) -> F {
+        // The moment of the line about p
+        let mp = self.moment - p.0.cross(self.direction);
+        mp.magnitude() / self.direction.magnitude()
+    }
+}
+
+impl<F: FullFloat> Line3<F> {
+    /// The permuted inner product of two lines.
+    ///
+    /// Zero when the lines intersect (or are parallel); otherwise its sign
+    /// tells which way `other` passes around `self`.  A line passes through
+    /// a triangle exactly when this has the same sign against all three
+    /// directed edges, which makes for a robust ray-triangle test.
+    pub fn side(&self, other: &Line3<F>) -> F {
+        self.direction.dot(other.moment) + other.direction.dot(self.moment)
+    }
+}
+
+impl<F: FullFloat> Line3<F> {
+    /// The shortest distance between two lines
+    pub fn distance(&self, other: &Line3<F>) -> F {
+        let n = self.direction.cross(other.direction);
+        let nn = n.magnitude();
+        if nn == F::zero() {
+            // Parallel: the distance from any point of one to the other
+            return self.distance_to_point(&other.point());
+        }
+        self.side(other).abs() / nn
+    }
+}
+
+impl<F: FullFloat> Line3<F> {
+    /// The closest points on `self` and on `other` respectively, or None if
+    /// the lines are parallel
+    pub fn closest_points(&self, other: &Line3<F>) -> Option<(Point3<F>, Point3<F>)> {
+        let (d1, d2) = (self.direction, other.direction);
+        let n = d1.cross(d2);
+        let nn = n.squared_magnitude();
+        if nn == F::zero() {
+            return None;
+        }
+        let r = other.point() - self.point();
+        let t1 = r.cross(d2).dot(n) / nn;
+        let t2 = r.cross(d1).dot(n) / nn;
+        Some((Point3(self.point().0 + d1 * t1),
+              Point3(other.point().0 + d2 * t2)))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Planes
+
+impl<F: FullFloat> Line3<F> {
+    /// Where the line crosses the plane, or None if it is parallel to it
+    pub fn intersect_plane(&self, plane: &Plane<F>) -> Option<Point3<F>> {
+        let n: Vec3<F> = *plane.normal;
+        let denom = n.dot(self.direction);
+        if denom == F::zero() {
+            return None;
+        }
+        Some(Point3((n.cross(self.moment) + self.direction * plane.offset) / denom))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Transforms
+
+impl<F: FullFloat> Line3<F> {
+    /// Transform by a homogeneous matrix.  The line's direction is preserved
+    /// by transforming two of its points.
+    pub fn transform(&self, m: &Mat4<F>) -> Line3<F> {
+        let p = self.point();
+        let a = m * Vec4::new(p.x, p.y, p.z, F::one());
+        let q = p.0 + self.direction;
+        let b = m * Vec4::new(q.x, q.y, q.z, F::one());
+        let a = Point3::new(a.x / a.w, a.y / a.w, a.z / a.w);
+        let b = Point3::new(b.x / b.w, b.y / b.w, b.z / b.w);
+        Line3::from_points(&a, &b)
+    }
+}
+
+impl<F: FullFloat> Line3<F> {
+    /// Transform from the local space of `position` into its parent space
+    pub fn transform_position(&self, position: &Position<F>) -> Line3<F> {
+        let d = position.ori.rotate(self.direction);
+        let t = position.point.0;
+        Line3 {
+            direction: d,
+            moment: position.ori.rotate(self.moment) + t.cross(d),
+        }
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Line3<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.direction.approx_eq(&other.direction, margin)
+            && self.moment.approx_eq(&other.moment, margin)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Vec3, Point3, Direction3, Mat3, Mat4, NQuat, Angle, Position, Plane};
+    use super::Line3;
+
+    const MARGIN: (f64, i64) = (1e-12, 8);
+
+    #[test]
+    fn test_construction() {
+        let a = Point3::new(1.0_f64, 2.0, 3.0);
+        let b = Point3::new(4.0, 0.0, -1.0);
+        let l = Line3::from_points(&a, &b);
+        let d: Direction3<f64> = From::from(b - a);
+        let l2 = Line3::from_point_direction(&b, &d);
+        // same line, different scale of direction
+        let scale = l.direction.magnitude();
+        assert!(l.moment.approx_eq(&(l2.moment * scale), MARGIN));
+
+        // the closest point to the origin is on the line and perpendicular
+        let p = l.point();
+        assert!(l.distance_to_point(&p) < 1e-12);
+        assert!(p.0.dot(l.direction).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_distance_closest() {
+        // x axis, and a line parallel to y at height 2 crossing x = 3
+        let l1 = Line3::from_points(&Point3::new(0.0_f64, 0.0, 0.0), &Point3::new(1.0, 0.0, 0.0));
+        let l2 = Line3::from_points(&Point3::new(3.0_f64, 5.0, 2.0), &Point3::new(3.0, 7.0, 2.0));
+        assert!((l1.distance(&l2) - 2.0).abs() < 1e-12);
+        let (p1, p2) = l1.closest_points(&l2).unwrap();
+        assert!(p1.approx_eq(&Point3::new(3.0, 0.0, 0.0), MARGIN));
+        assert!(p2.approx_eq(&Point3::new(3.0, 0.0, 2.0), MARGIN));
+
+        // parallel
+        let l3 = Line3::from_points(&Point3::new(0.0_f64, 1.0, 1.0), &Point3::new(5.0, 1.0, 1.0));
+        assert!((l1.distance(&l3) - 2.0_f64.sqrt()).abs() < 1e-12);
+        assert!(l1.closest_points(&l3).is_none());
+
+        assert!((l1.distance_to_point(&Point3::new(7.0, 3.0, 4.0)) - 5.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_side_triangle() {
+        let a = Point3::new(0.0_f64, 0.0, 0.0);
+        let b = Point3::new(1.0, 0.0, 0.0);
+        let c = Point3::new(0.0, 1.0, 0.0);
+        let edges = [Line3::from_points(&a, &b), Line3::from_points(&b, &c),
+                     Line3::from_points(&c, &a)];
+        let hits = |ray: &Line3<f64>| {
+            let s: Vec<f64> = edges.iter().map(|e| e.side(ray)).collect();
+            s.iter().all(|x| *x > 0.0) || s.iter().all(|x| *x < 0.0)
+        };
+        let down = Direction3::new_isnormal(0.0, 0.0, -1.0);
+        assert!(hits(&Line3::from_point_direction(&Point3::new(0.25, 0.25, 5.0), &down)));
+        assert!(!hits(&Line3::from_point_direction(&Point3::new(0.75, 0.75, 5.0), &down)));
+        assert!(!hits(&Line3::from_point_direction(&Point3::new(-0.1, 0.5, 5.0), &down)));
+
+        // opposite orientation flips every sign
+        let ray = Line3::from_point_direction(&Point3::new(0.25, 0.25, 5.0), &down);
+        assert!(edges[0].side(&ray) * edges[0].side(&ray.reverse()) < 0.0);
+
+        // intersecting lines have zero side
+        assert_eq!(edges[0].side(&edges[1]), 0.0);
+    }
+
+    #[test]
+    fn test_intersect_plane() {
+        let plane = Plane::new(Direction3::new_isnormal(0.0_f64, 0.0, 1.0), 2.0);
+        let l = Line3::from_points(&Point3::new(1.0, 1.0, 0.0), &Point3::new(2.0, 3.0, 1.0));
+        let p = l.intersect_plane(&plane).unwrap();
+        assert!(p.approx_eq(&Point3::new(3.0, 5.0, 2.0), MARGIN));
+        assert!(plane.signed_distance(&p).abs() < 1e-12);
+
+        let flat = Line3::from_points(&Point3::new(0.0, 0.0, 0.0), &Point3::new(1.0, 0.0, 0.0));
+        assert!(flat.intersect_plane(&plane).is_none());
+    }
+
+    #[test]
+    fn test_transform() {
+        let a = Point3::new(1.0_f64, 2.0, 3.0);
+        let b = Point3::new(-1.0, 0.5, 2.0);
+        let l = Line3::from_points(&a, &b);
+
+        let axis: Direction3<f64> = From::from(Vec3::new(1.0, -1.0, 2.0));
+        let ori = NQuat::from_axis_angle(&axis, &Angle::from_degrees(50.0));
+        let position = Position { point: Point3::new(10.0, -3.0, 0.5), ori };
+        let move_point = |p: Point3<f64>| Point3(ori.rotate(p.0) + position.point.0);
+
+        let expected = Line3::from_points(&move_point(a), &move_point(b));
+        assert!(l.transform_position(&position).approx_eq(&expected, MARGIN));
+
+        let r: Mat3<f64> = From::from(ori);
+        let m = Mat4::from_mat3(r, position.point);
+        let t = l.transform(&m);
+        // same line, up to the scale of the direction
+        let scale = expected.direction.magnitude() / t.direction.magnitude();
+        assert!((t.direction * scale).approx_eq(&expected.direction, MARGIN));
+        assert!((t.moment * scale).approx_eq(&expected.moment, (1e-12, 64)));
+    }
+}
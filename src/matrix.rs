@@ -1,11 +1,15 @@
 
 use num_traits::NumCast;
-use std::ops::{Index, IndexMut, Mul, Add};
+use std::ops::{Index, IndexMut, Mul, Add, Sub, Neg, Div,
+               AddAssign, SubAssign, MulAssign, DivAssign};
 use std::default::Default;
+use std::fmt;
+use std::str::FromStr;
 use serde::{Serialize, Deserialize};
 use float_cmp::ApproxEq;
-use crate::vector::{Vec2, Vec3, Vec4, Direction3, Point3};
-use crate::{Angle, FullFloat};
+use crate::vector::{Vec2, Vec3, Vec4, Direction2, Direction3, Point2, Point3};
+use crate::{Angle, FullFloat, MathError};
+use crate::parse::{ParseError, parse_floats, write_matrix};
 
 // NOTE: we store matrices in column-major order, which means we pre-multiply.
 // This is traditional so matrices directly copied to the GPU will work with
@@ -165,13 +169,14 @@ impl<F: FullFloat> Mat2<F> {
     #[inline]
     pub fn from_cols(x: Vec2<F>, y: Vec2<F>) -> Mat2<F>
     {
-        Mat2 { x: x, y: y }
+        Mat2 { x, y }
     }
 }
 
 impl<F: FullFloat> Mat3<F> {
     /// Create a new 3x3 Matrix. Specify parameters in row-major order
     /// (as typically written on paper and in math texts)
+    #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn new(r0c0: F, r0c1: F, r0c2: F,
                r1c0: F, r1c1: F, r1c2: F,
@@ -187,13 +192,14 @@ impl<F: FullFloat> Mat3<F> {
     #[inline]
     pub fn from_cols(x: Vec3<F>, y: Vec3<F>, z: Vec3<F>) -> Mat3<F>
     {
-        Mat3 { x: x, y: y, z: z }
+        Mat3 { x, y, z }
     }
 }
 
 impl<F: FullFloat> Mat4<F> {
     /// Create a new 4x4 Matrix. Specify parameters in row-major order
     /// (as typically written on paper and in math texts)
+    #[allow(clippy::too_many_arguments)]
     #[inline]
     pub fn new(r0c0: F, r0c1: F, r0c2: F, r0c3: F,
                r1c0: F, r1c1: F, r1c2: F, r1c3: F,
@@ -211,7 +217,7 @@ impl<F: FullFloat> Mat4<F> {
     #[inline]
     pub fn from_cols(x: Vec4<F>, y: Vec4<F>, z: Vec4<F>, p: Vec4<F>) -> Mat4<F>
     {
-        Mat4 { x: x, y: y, z: z, p: p }
+        Mat4 { x, y, z, p }
     }
 }
 
@@ -428,7 +434,7 @@ impl<F: FullFloat> Mat4<F> {
         let d = self.determinant();
         if d == F::zero() { return None; }
         let id = F::one() / d;
-        let mut t = self.clone();
+        let mut t = *self;
         t.transpose();
         let cf = |i, j| {
             let mat = match i {
@@ -449,9 +455,105 @@ impl<F: FullFloat> Mat4<F> {
     }
 }
 
+// These distinguish a singular matrix from one with NaN or infinite
+// elements, which `inverse()` would either reject or pass through as NaNs.
+// The matrix is scaled by its largest element before inverting, so that
+// the determinant of a well-conditioned matrix with very large or very
+// small elements cannot overflow or underflow.
+
+macro_rules! impl_try_inverse {
+    ($MatN:ident, $n:expr) => {
+        impl<F: FullFloat> $MatN<F> {
+            /// The inverse, or `Singular`, or `NonFinite` if any element is
+            /// NaN or infinite
+            pub fn try_inverse(&self) -> Result<$MatN<F>, MathError> {
+                let mut big = F::zero();
+                for row in 0..$n {
+                    for col in 0..$n {
+                        let e = self[(row, col)];
+                        if !e.is_finite() { return Err(MathError::NonFinite); }
+                        big = big.max(e.abs());
+                    }
+                }
+                if big == F::zero() { return Err(MathError::Singular); }
+                let inv = (self / big).inverse().ok_or(MathError::Singular)?;
+                Ok(&inv / big)
+            }
+        }
+    }
+}
+
+impl_try_inverse!(Mat2, 2);
+impl_try_inverse!(Mat3, 3);
+impl_try_inverse!(Mat4, 4);
+
+// -- cofactor and adjugate ---------------------------------------------------
+
+impl<F: FullFloat> Mat2<F> {
+    /// The matrix of cofactors
+    pub fn cofactor(&self) -> Mat2<F> {
+        Mat2::new( self.y.y, -self.x.y,
+                   -self.y.x, self.x.x )
+    }
+
+    /// The transpose of the cofactor matrix, which is the inverse times
+    /// the determinant but exists even for a singular matrix
+    pub fn adjugate(&self) -> Mat2<F> {
+        let mut m = self.cofactor();
+        m.transpose();
+        m
+    }
+}
+
+impl<F: FullFloat> Mat3<F> {
+    /// The matrix of cofactors
+    pub fn cofactor(&self) -> Mat3<F> {
+        Mat3::from_cols(self.y.cross(self.z),
+                        self.z.cross(self.x),
+                        self.x.cross(self.y))
+    }
+
+    /// The transpose of the cofactor matrix, which is the inverse times
+    /// the determinant but exists even for a singular matrix
+    pub fn adjugate(&self) -> Mat3<F> {
+        let mut m = self.cofactor();
+        m.transpose();
+        m
+    }
+}
+
+impl<F: FullFloat> Mat4<F> {
+    /// The matrix of cofactors
+    pub fn cofactor(&self) -> Mat4<F> {
+        let mut out = Mat4::zero();
+        for row in 0..4 {
+            for col in 0..4 {
+                // element (r, c) of the minor, skipping this row and column
+                let m = |r: usize, c: usize| {
+                    self[(if r < row { r } else { r + 1 }, if c < col { c } else { c + 1 })]
+                };
+                let minor = Mat3::new(m(0, 0), m(0, 1), m(0, 2),
+                                      m(1, 0), m(1, 1), m(1, 2),
+                                      m(2, 0), m(2, 1), m(2, 2));
+                let d = minor.determinant();
+                out[(row, col)] = if (row + col) & 1 == 1 { -d } else { d };
+            }
+        }
+        out
+    }
+
+    /// The transpose of the cofactor matrix, which is the inverse times
+    /// the determinant but exists even for a singular matrix
+    pub fn adjugate(&self) -> Mat4<F> {
+        let mut m = self.cofactor();
+        m.transpose();
+        m
+    }
+}
+
 // -- add ---------------------------------------------------------------------
 
-impl<'a, 'b, F: FullFloat> Add<&'b Mat2<F>> for &'a Mat2<F> {
+impl<F: FullFloat> Add<&Mat2<F>> for &Mat2<F> {
     type Output = Mat2<F>;
 
     #[inline]
@@ -461,7 +563,7 @@ impl<'a, 'b, F: FullFloat> Add<&'b Mat2<F>> for &'a Mat2<F> {
     }
 }
 
-impl<'a, 'b, F: FullFloat> Add<&'b Mat3<F>> for &'a Mat3<F> {
+impl<F: FullFloat> Add<&Mat3<F>> for &Mat3<F> {
     type Output = Mat3<F>;
 
     #[inline]
@@ -472,7 +574,7 @@ impl<'a, 'b, F: FullFloat> Add<&'b Mat3<F>> for &'a Mat3<F> {
     }
 }
 
-impl<'a, 'b, F: FullFloat> Add<&'b Mat4<F>> for &'a Mat4<F> {
+impl<F: FullFloat> Add<&Mat4<F>> for &Mat4<F> {
     type Output = Mat4<F>;
 
     #[inline]
@@ -481,13 +583,71 @@ impl<'a, 'b, F: FullFloat> Add<&'b Mat4<F>> for &'a Mat4<F> {
             self.x.x + rhs.x.x, self.y.x + rhs.y.x, self.z.x + rhs.z.x, self.p.x + rhs.p.x,
             self.x.y + rhs.x.y, self.y.y + rhs.y.y, self.z.y + rhs.z.y, self.p.y + rhs.p.y,
             self.x.z + rhs.x.z, self.y.z + rhs.y.z, self.z.z + rhs.z.z, self.p.z + rhs.p.z,
-            self.x.w + rhs.x.w, self.y.w + rhs.y.z, self.z.w + rhs.z.w, self.p.w + rhs.p.w )
+            self.x.w + rhs.x.w, self.y.w + rhs.y.w, self.z.w + rhs.z.w, self.p.w + rhs.p.w )
+    }
+}
+
+// -- subtract ----------------------------------------------------------------
+
+impl<F: FullFloat> Sub<&Mat2<F>> for &Mat2<F> {
+    type Output = Mat2<F>;
+
+    #[inline]
+    fn sub(self, rhs: &Mat2<F>) -> Mat2<F> {
+        Mat2::from_cols(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<F: FullFloat> Sub<&Mat3<F>> for &Mat3<F> {
+    type Output = Mat3<F>;
+
+    #[inline]
+    fn sub(self, rhs: &Mat3<F>) -> Mat3<F> {
+        Mat3::from_cols(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+impl<F: FullFloat> Sub<&Mat4<F>> for &Mat4<F> {
+    type Output = Mat4<F>;
+
+    #[inline]
+    fn sub(self, rhs: &Mat4<F>) -> Mat4<F> {
+        Mat4::from_cols(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z, self.p - rhs.p)
+    }
+}
+
+// -- negate ------------------------------------------------------------------
+
+impl<F: FullFloat> Neg for &Mat2<F> {
+    type Output = Mat2<F>;
+
+    #[inline]
+    fn neg(self) -> Mat2<F> {
+        Mat2::from_cols(-self.x, -self.y)
+    }
+}
+
+impl<F: FullFloat> Neg for &Mat3<F> {
+    type Output = Mat3<F>;
+
+    #[inline]
+    fn neg(self) -> Mat3<F> {
+        Mat3::from_cols(-self.x, -self.y, -self.z)
+    }
+}
+
+impl<F: FullFloat> Neg for &Mat4<F> {
+    type Output = Mat4<F>;
+
+    #[inline]
+    fn neg(self) -> Mat4<F> {
+        Mat4::from_cols(-self.x, -self.y, -self.z, -self.p)
     }
 }
 
 // -- multiply by scalar ------------------------------------------------------
 
-impl<'a, F: FullFloat> Mul<F> for &'a Mat2<F> {
+impl<F: FullFloat> Mul<F> for &Mat2<F> {
     type Output = Mat2<F>;
 
     #[inline]
@@ -497,7 +657,7 @@ impl<'a, F: FullFloat> Mul<F> for &'a Mat2<F> {
     }
 }
 
-impl<'a, F: FullFloat> Mul<F> for &'a Mat3<F> {
+impl<F: FullFloat> Mul<F> for &Mat3<F> {
     type Output = Mat3<F>;
 
     #[inline]
@@ -508,7 +668,7 @@ impl<'a, F: FullFloat> Mul<F> for &'a Mat3<F> {
     }
 }
 
-impl<'a, F: FullFloat> Mul<F> for &'a Mat4<F> {
+impl<F: FullFloat> Mul<F> for &Mat4<F> {
     type Output = Mat4<F>;
 
     #[inline]
@@ -520,9 +680,38 @@ impl<'a, F: FullFloat> Mul<F> for &'a Mat4<F> {
     }
 }
 
+// -- divide by scalar --------------------------------------------------------
+
+impl<F: FullFloat> Div<F> for &Mat2<F> {
+    type Output = Mat2<F>;
+
+    #[inline]
+    fn div(self, rhs: F) -> Mat2<F> {
+        Mat2::from_cols(self.x / rhs, self.y / rhs)
+    }
+}
+
+impl<F: FullFloat> Div<F> for &Mat3<F> {
+    type Output = Mat3<F>;
+
+    #[inline]
+    fn div(self, rhs: F) -> Mat3<F> {
+        Mat3::from_cols(self.x / rhs, self.y / rhs, self.z / rhs)
+    }
+}
+
+impl<F: FullFloat> Div<F> for &Mat4<F> {
+    type Output = Mat4<F>;
+
+    #[inline]
+    fn div(self, rhs: F) -> Mat4<F> {
+        Mat4::from_cols(self.x / rhs, self.y / rhs, self.z / rhs, self.p / rhs)
+    }
+}
+
 // -- multiply by matrix ------------------------------------------------------
 
-impl<'a, 'b, F: FullFloat> Mul<&'b Mat2<F>> for &'a Mat2<F> {
+impl<F: FullFloat> Mul<&Mat2<F>> for &Mat2<F> {
     type Output = Mat2<F>;
 
     #[inline]
@@ -533,7 +722,7 @@ impl<'a, 'b, F: FullFloat> Mul<&'b Mat2<F>> for &'a Mat2<F> {
     }
 }
 
-impl<'a, 'b, F: FullFloat> Mul<&'b Mat3<F>> for &'a Mat3<F> {
+impl<F: FullFloat> Mul<&Mat3<F>> for &Mat3<F> {
     type Output = Mat3<F>;
 
     #[inline]
@@ -553,7 +742,7 @@ impl<'a, 'b, F: FullFloat> Mul<&'b Mat3<F>> for &'a Mat3<F> {
     }
 }
 
-impl<'a, 'b, F: FullFloat> Mul<&'b Mat4<F>> for &'a Mat4<F> {
+impl<F: FullFloat> Mul<&Mat4<F>> for &Mat4<F> {
     type Output = Mat4<F>;
 
     #[inline]
@@ -583,7 +772,7 @@ impl<'a, 'b, F: FullFloat> Mul<&'b Mat4<F>> for &'a Mat4<F> {
 
 // -- multiply by vector ------------------------------------------------------
 
-impl<'a, 'b, F: FullFloat> Mul<&'a Vec2<F>> for &'b Mat2<F> {
+impl<F: FullFloat> Mul<&Vec2<F>> for &Mat2<F> {
     type Output = Vec2<F>;
 
     #[inline]
@@ -593,7 +782,7 @@ impl<'a, 'b, F: FullFloat> Mul<&'a Vec2<F>> for &'b Mat2<F> {
     }
 }
 
-impl<'a, 'b, F: FullFloat> Mul<&'a Vec3<F>> for &'b Mat3<F> {
+impl<F: FullFloat> Mul<&Vec3<F>> for &Mat3<F> {
     type Output = Vec3<F>;
 
     #[inline]
@@ -604,7 +793,7 @@ impl<'a, 'b, F: FullFloat> Mul<&'a Vec3<F>> for &'b Mat3<F> {
     }
 }
 
-impl<'a, 'b, F: FullFloat> Mul<&'a Vec4<F>> for &'b Mat4<F> {
+impl<F: FullFloat> Mul<&Vec4<F>> for &Mat4<F> {
     type Output = Vec4<F>;
 
     #[inline]
@@ -618,7 +807,7 @@ impl<'a, 'b, F: FullFloat> Mul<&'a Vec4<F>> for &'b Mat4<F> {
 
 // -- multiply vector by matrix -----------------------------------------------
 
-impl<'a, 'b, F: FullFloat> Mul<&'a Mat2<F>> for &'a Vec2<F> {
+impl<F: FullFloat> Mul<&Mat2<F>> for &Vec2<F> {
     type Output = Vec2<F>;
 
     #[inline]
@@ -628,7 +817,7 @@ impl<'a, 'b, F: FullFloat> Mul<&'a Mat2<F>> for &'a Vec2<F> {
     }
 }
 
-impl<'a, 'b, F: FullFloat> Mul<&'a Mat3<F>> for &'a Vec3<F> {
+impl<F: FullFloat> Mul<&Mat3<F>> for &Vec3<F> {
     type Output = Vec3<F>;
 
     #[inline]
@@ -639,7 +828,7 @@ impl<'a, 'b, F: FullFloat> Mul<&'a Mat3<F>> for &'a Vec3<F> {
     }
 }
 
-impl<'a, 'b, F: FullFloat> Mul<&'a Mat4<F>> for &'a Vec4<F> {
+impl<F: FullFloat> Mul<&Mat4<F>> for &Vec4<F> {
     type Output = Vec4<F>;
 
     #[inline]
@@ -651,6 +840,274 @@ impl<'a, 'b, F: FullFloat> Mul<&'a Mat4<F>> for &'a Vec4<F> {
     }
 }
 
+// -- transform points, vectors and directions --------------------------------
+
+impl<F: FullFloat> Mat4<F> {
+    /// Transform a point (w = 1) by an affine matrix.  The bottom row is
+    /// ignored; for projective matrices use `project_point`.
+    pub fn transform_point(&self, p: &Point3<F>) -> Point3<F> {
+        Point3(self.as_mat3() * p.0 + self.p.truncate_w())
+    }
+}
+
+impl<F: FullFloat> Mat4<F> {
+    /// Transform a vector (w = 0).  Vectors are not translated.
+    pub fn transform_vector(&self, v: &Vec3<F>) -> Vec3<F> {
+        self.as_mat3() * v
+    }
+}
+
+impl<F: FullFloat> Mat4<F> {
+    /// Transform a direction, renormalizing the result.  If the matrix
+    /// takes the direction to zero the result has NaN components.
+    ///
+    /// This is correct for tangents and other directions along a surface.
+    /// Surface normals must instead be transformed by the `normal_matrix` of
+    /// the upper 3x3 whenever the matrix has non-uniform scale or shear.
+    pub fn transform_direction(&self, d: &Direction3<F>) -> Direction3<F> {
+        From::from(self.transform_vector(d))
+    }
+}
+
+impl<F: FullFloat> Mat3<F> {
+    /// The inverse-transpose, which transforms surface normals so they stay
+    /// perpendicular to the transformed surface.  Returns None if the matrix
+    /// is singular.
+    pub fn normal_matrix(&self) -> Option<Mat3<F>> {
+        let mut m = self.inverse()?;
+        m.transpose();
+        Some(m)
+    }
+}
+
+// -- multiply by point and direction -----------------------------------------
+
+// Points and directions are transformed like vectors by Mat2 and Mat3.  A
+// Mat4 treats them as `transform_point` and `transform_direction` do.
+
+impl<F: FullFloat> Mul<&Point2<F>> for &Mat2<F> {
+    type Output = Point2<F>;
+
+    #[inline]
+    fn mul(self, rhs: &Point2<F>) -> Point2<F> {
+        Point2(self * rhs.0)
+    }
+}
+
+impl<F: FullFloat> Mul<&Point3<F>> for &Mat3<F> {
+    type Output = Point3<F>;
+
+    #[inline]
+    fn mul(self, rhs: &Point3<F>) -> Point3<F> {
+        Point3(self * rhs.0)
+    }
+}
+
+impl<F: FullFloat> Mul<&Point3<F>> for &Mat4<F> {
+    type Output = Point3<F>;
+
+    #[inline]
+    fn mul(self, rhs: &Point3<F>) -> Point3<F> {
+        self.transform_point(rhs)
+    }
+}
+
+/// Renormalizes the result.  A singular or projecting matrix can take the
+/// direction to zero, which gives NaN components; use
+/// `Direction2::try_from_vec(m * *d)` when that may happen.
+impl<F: FullFloat> Mul<&Direction2<F>> for &Mat2<F> {
+    type Output = Direction2<F>;
+
+    #[inline]
+    fn mul(self, rhs: &Direction2<F>) -> Direction2<F> {
+        From::from(self * **rhs)
+    }
+}
+
+/// Renormalizes the result.  A singular or projecting matrix can take the
+/// direction to zero, which gives NaN components; use
+/// `Direction3::try_from_vec(m * *d)` when that may happen.
+impl<F: FullFloat> Mul<&Direction3<F>> for &Mat3<F> {
+    type Output = Direction3<F>;
+
+    #[inline]
+    fn mul(self, rhs: &Direction3<F>) -> Direction3<F> {
+        From::from(self * **rhs)
+    }
+}
+
+/// Renormalizes the result.  A singular or projecting matrix can take the
+/// direction to zero, which gives NaN components; use
+/// `Direction3::try_from_vec(m.transform_vector(&d))` when that may happen.
+impl<F: FullFloat> Mul<&Direction3<F>> for &Mat4<F> {
+    type Output = Direction3<F>;
+
+    #[inline]
+    fn mul(self, rhs: &Direction3<F>) -> Direction3<F> {
+        self.transform_direction(rhs)
+    }
+}
+
+// -- owned operands ----------------------------------------------------------
+
+// The operators above are implemented on references, so that large
+// matrices need not be copied.  These forward the other combinations of
+// owned and borrowed operands to them.
+
+macro_rules! forward_binop {
+    ($Op:ident, $op:ident, $Lhs:ty, $Rhs:ty, $Output:ty) => {
+        impl<F: FullFloat> $Op<$Rhs> for $Lhs {
+            type Output = $Output;
+
+            #[inline]
+            fn $op(self, rhs: $Rhs) -> $Output {
+                <&$Lhs as $Op<&$Rhs>>::$op(&self, &rhs)
+            }
+        }
+
+        impl<'a, F: FullFloat> $Op<&'a $Rhs> for $Lhs {
+            type Output = $Output;
+
+            #[inline]
+            fn $op(self, rhs: &$Rhs) -> $Output {
+                <&$Lhs as $Op<&$Rhs>>::$op(&self, rhs)
+            }
+        }
+
+        impl<'a, F: FullFloat> $Op<$Rhs> for &'a $Lhs {
+            type Output = $Output;
+
+            #[inline]
+            fn $op(self, rhs: $Rhs) -> $Output {
+                <&$Lhs as $Op<&$Rhs>>::$op(self, &rhs)
+            }
+        }
+    }
+}
+
+macro_rules! forward_scalar_ops {
+    ($MatN:ident) => {
+        impl<F: FullFloat> Mul<F> for $MatN<F> {
+            type Output = $MatN<F>;
+
+            #[inline]
+            fn mul(self, rhs: F) -> $MatN<F> {
+                &self * rhs
+            }
+        }
+
+        impl<F: FullFloat> Div<F> for $MatN<F> {
+            type Output = $MatN<F>;
+
+            #[inline]
+            fn div(self, rhs: F) -> $MatN<F> {
+                &self / rhs
+            }
+        }
+
+        impl<F: FullFloat> Neg for $MatN<F> {
+            type Output = $MatN<F>;
+
+            #[inline]
+            fn neg(self) -> $MatN<F> {
+                -&self
+            }
+        }
+    }
+}
+
+macro_rules! impl_assign_ops {
+    ($MatN:ident) => {
+        impl<F: FullFloat> AddAssign<$MatN<F>> for $MatN<F> {
+            #[inline]
+            fn add_assign(&mut self, rhs: $MatN<F>) {
+                *self = &*self + &rhs;
+            }
+        }
+
+        impl<'a, F: FullFloat> AddAssign<&'a $MatN<F>> for $MatN<F> {
+            #[inline]
+            fn add_assign(&mut self, rhs: &$MatN<F>) {
+                *self = &*self + rhs;
+            }
+        }
+
+        impl<F: FullFloat> SubAssign<$MatN<F>> for $MatN<F> {
+            #[inline]
+            fn sub_assign(&mut self, rhs: $MatN<F>) {
+                *self = &*self - &rhs;
+            }
+        }
+
+        impl<'a, F: FullFloat> SubAssign<&'a $MatN<F>> for $MatN<F> {
+            #[inline]
+            fn sub_assign(&mut self, rhs: &$MatN<F>) {
+                *self = &*self - rhs;
+            }
+        }
+
+        /// `a *= b` sets `a` to `a * b`, so `b` applies first
+        impl<F: FullFloat> MulAssign<$MatN<F>> for $MatN<F> {
+            #[inline]
+            fn mul_assign(&mut self, rhs: $MatN<F>) {
+                *self = &*self * &rhs;
+            }
+        }
+
+        /// `a *= b` sets `a` to `a * b`, so `b` applies first
+        impl<'a, F: FullFloat> MulAssign<&'a $MatN<F>> for $MatN<F> {
+            #[inline]
+            fn mul_assign(&mut self, rhs: &$MatN<F>) {
+                *self = &*self * rhs;
+            }
+        }
+
+        impl<F: FullFloat> MulAssign<F> for $MatN<F> {
+            #[inline]
+            fn mul_assign(&mut self, rhs: F) {
+                *self = &*self * rhs;
+            }
+        }
+
+        impl<F: FullFloat> DivAssign<F> for $MatN<F> {
+            #[inline]
+            fn div_assign(&mut self, rhs: F) {
+                *self = &*self / rhs;
+            }
+        }
+    }
+}
+
+forward_binop!(Add, add, Mat2<F>, Mat2<F>, Mat2<F>);
+forward_binop!(Add, add, Mat3<F>, Mat3<F>, Mat3<F>);
+forward_binop!(Add, add, Mat4<F>, Mat4<F>, Mat4<F>);
+forward_binop!(Sub, sub, Mat2<F>, Mat2<F>, Mat2<F>);
+forward_binop!(Sub, sub, Mat3<F>, Mat3<F>, Mat3<F>);
+forward_binop!(Sub, sub, Mat4<F>, Mat4<F>, Mat4<F>);
+forward_binop!(Mul, mul, Mat2<F>, Mat2<F>, Mat2<F>);
+forward_binop!(Mul, mul, Mat3<F>, Mat3<F>, Mat3<F>);
+forward_binop!(Mul, mul, Mat4<F>, Mat4<F>, Mat4<F>);
+forward_binop!(Mul, mul, Mat2<F>, Vec2<F>, Vec2<F>);
+forward_binop!(Mul, mul, Mat3<F>, Vec3<F>, Vec3<F>);
+forward_binop!(Mul, mul, Mat4<F>, Vec4<F>, Vec4<F>);
+forward_binop!(Mul, mul, Vec2<F>, Mat2<F>, Vec2<F>);
+forward_binop!(Mul, mul, Vec3<F>, Mat3<F>, Vec3<F>);
+forward_binop!(Mul, mul, Vec4<F>, Mat4<F>, Vec4<F>);
+forward_binop!(Mul, mul, Mat2<F>, Point2<F>, Point2<F>);
+forward_binop!(Mul, mul, Mat3<F>, Point3<F>, Point3<F>);
+forward_binop!(Mul, mul, Mat4<F>, Point3<F>, Point3<F>);
+forward_binop!(Mul, mul, Mat2<F>, Direction2<F>, Direction2<F>);
+forward_binop!(Mul, mul, Mat3<F>, Direction3<F>, Direction3<F>);
+forward_binop!(Mul, mul, Mat4<F>, Direction3<F>, Direction3<F>);
+
+forward_scalar_ops!(Mat2);
+forward_scalar_ops!(Mat3);
+forward_scalar_ops!(Mat4);
+
+impl_assign_ops!(Mat2);
+impl_assign_ops!(Mat3);
+impl_assign_ops!(Mat4);
+
 // -- characteristic tests ----------------------------------------------------
 
 impl<F: FullFloat> Mat2<F> {
@@ -674,8 +1131,8 @@ impl<F: FullFloat> Mat4<F> {
     pub fn is_diagonal(&self) -> bool {
         self.x.y == F::zero() && self.x.z == F::zero() && self.x.w == F::zero() &&
             self.y.x == F::zero() && self.y.z == F::zero() && self.y.w == F::zero() &&
-            self.z.x == F::zero() && self.z.y == F::zero() && self.p.w == F::zero() &&
-            self.p.x == F::zero() && self.p.y == F::zero() && self.z.z == F::zero()
+            self.z.x == F::zero() && self.z.y == F::zero() && self.z.w == F::zero() &&
+            self.p.x == F::zero() && self.p.y == F::zero() && self.p.z == F::zero()
     }
 }
 
@@ -736,6 +1193,107 @@ impl<F: FullFloat> Mat4<F> {
     }
 }
 
+impl<F: FullFloat> Mat4<F> {
+    /// Whether the bottom row is exactly (0, 0, 0, 1), so that the matrix
+    /// has no perspective component
+    #[inline]
+    pub fn is_affine(&self) -> bool {
+        self.x.w == F::zero() && self.y.w == F::zero() &&
+            self.z.w == F::zero() && self.p.w == F::one()
+    }
+}
+
+// -- trace, norms and conditioning -------------------------------------------
+
+macro_rules! impl_mat_measures {
+    ($MatN:ident, $n:expr) => {
+        impl<F: FullFloat> $MatN<F> {
+            /// The sum of the diagonal elements
+            pub fn trace(&self) -> F {
+                (0..$n).fold(F::zero(), |acc, i| acc + self[(i, i)])
+            }
+        }
+
+        impl<F: FullFloat> $MatN<F> {
+            /// The square root of the sum of the squares of the elements
+            pub fn frobenius_norm(&self) -> F {
+                let mut sum = F::zero();
+                for row in 0..$n {
+                    for col in 0..$n {
+                        sum += self[(row, col)] * self[(row, col)];
+                    }
+                }
+                sum.sqrt()
+            }
+
+            /// The largest absolute column sum
+            pub fn norm_1(&self) -> F {
+                (0..$n).fold(F::zero(), |max, col| {
+                    max.max((0..$n).fold(F::zero(), |s, row| s + self[(row, col)].abs()))
+                })
+            }
+
+            /// The largest absolute row sum
+            pub fn norm_inf(&self) -> F {
+                (0..$n).fold(F::zero(), |max, row| {
+                    max.max((0..$n).fold(F::zero(), |s, col| s + self[(row, col)].abs()))
+                })
+            }
+
+            /// The condition number in the 1-norm, `‖A‖₁‖A⁻¹‖₁`.  It is at
+            /// least 1, and exactly 1 only for a scaled signed permutation
+            /// such as an axis-aligned rotation; for any other orthogonal
+            /// matrix it lies between 1 and the dimension (a 45° rotation in
+            /// the plane gives 2).  It grows as the matrix nears singularity;
+            /// roughly, its base ten logarithm is the number of digits lost
+            /// in solving with it.  Infinite if the matrix is singular.
+            pub fn condition_number(&self) -> F {
+                match self.inverse() {
+                    Some(inv) => self.norm_1() * inv.norm_1(),
+                    None => F::infinity(),
+                }
+            }
+        }
+
+        impl<F: FullFloat> $MatN<F> {
+            /// Whether the columns are orthonormal, so that the transpose is
+            /// the inverse, with each element of `AᵀA` within `tolerance` of
+            /// the identity
+            pub fn is_orthogonal(&self, tolerance: F) -> bool {
+                for i in 0..$n {
+                    for j in 0..$n {
+                        let dot = (0..$n).fold(F::zero(), |s, k| s + self[(k, i)] * self[(k, j)]);
+                        let expected = if i == j { F::one() } else { F::zero() };
+                        let error = (dot - expected).abs();
+                        if error.is_nan() || error > tolerance {
+                            return false;
+                        }
+                    }
+                }
+                true
+            }
+
+            /// Whether the matrix is orthogonal with a determinant of +1, a
+            /// pure rotation with no scale or reflection
+            pub fn is_rotation(&self, tolerance: F) -> bool {
+                self.is_orthogonal(tolerance) &&
+                    (self.determinant() - F::one()).abs() <= tolerance
+            }
+
+            /// Whether the magnitude of the determinant exceeds `tolerance`.
+            /// The determinant scales with the matrix, so for a test that
+            /// ignores scale use `condition_number()`.
+            pub fn is_invertible(&self, tolerance: F) -> bool {
+                self.determinant().abs() > tolerance
+            }
+        }
+    }
+}
+
+impl_mat_measures!(Mat2, 2);
+impl_mat_measures!(Mat3, 3);
+impl_mat_measures!(Mat4, 4);
+
 // -- mat4 components ---------------------------------------------------------
 
 impl<F: FullFloat> Mat4<F> {
@@ -974,6 +1532,97 @@ impl<F: FullFloat> Mat3<F> {
     }
 }
 
+// -- Orthonormalization -----------------------------------------------------
+
+impl<F: FullFloat> Mat3<F> {
+    /// Make the columns orthonormal by Gram-Schmidt, correcting the drift
+    /// that accumulates in a rotation matrix over many multiplications.
+    /// The X column keeps its direction, Y stays in the plane of X and Y,
+    /// and Z stays on the same side, so handedness is preserved.
+    ///
+    /// Fails with `Singular`, leaving the matrix unchanged, if the columns
+    /// are linearly dependent to within rounding, or with `NonFinite`.
+    pub fn orthonormalize(&mut self) -> Result<(), MathError> {
+        let x = independent(self.x, &[])?;
+        let y = independent(self.y, &[x])?;
+        let z = independent(self.z, &[x, y])?;
+        *self = Mat3::from_cols(x, y, z);
+        Ok(())
+    }
+}
+
+// Below this fraction of its length, what is left of a vector after
+// projecting out others is rounding noise, with no meaningful direction
+fn dependence_tolerance<F: FullFloat>() -> F {
+    let k: F = NumCast::from(16.0_f32).unwrap();
+    F::epsilon() * k
+}
+
+// The unit vector along what is left of `v` after projecting out the
+// orthonormal `basis`.  Projecting twice makes it orthogonal to working
+// precision even when the first pass cancels most of `v`.
+fn independent<F: FullFloat>(v: Vec3<F>, basis: &[Vec3<F>]) -> Result<Vec3<F>, MathError> {
+    let mut r = v;
+    for _ in 0..2 {
+        for b in basis {
+            r -= *b * b.dot(r);
+        }
+    }
+    if r.magnitude() <= dependence_tolerance::<F>() * v.magnitude() {
+        return Err(MathError::Singular);
+    }
+    Ok(*Direction3::try_from_vec(r)?)
+}
+
+impl<F: FullFloat> Mat4<F> {
+    /// Orthonormalize the upper 3x3 (see `Mat3::orthonormalize`), which
+    /// also removes any scale.  The translation is kept.
+    pub fn orthonormalize_rotation(&mut self) -> Result<(), MathError> {
+        let mut m = self.as_mat3();
+        m.orthonormalize()?;
+        self.x = Vec4::new(m.x.x, m.x.y, m.x.z, self.x.w);
+        self.y = Vec4::new(m.y.x, m.y.y, m.y.z, self.y.w);
+        self.z = Vec4::new(m.z.x, m.z.y, m.z.z, self.z.w);
+        Ok(())
+    }
+}
+
+impl<F: FullFloat> Mat3<F> {
+    /// The tangent frame of a triangle for normal mapping: a matrix whose
+    /// columns are the tangent (the direction of increasing U), bitangent
+    /// (increasing V) and normal, taking tangent space to the triangle's
+    /// space.
+    ///
+    /// The frame is orthonormal, the tangent being made perpendicular to
+    /// the normal.  Where the UVs are mirrored the bitangent is flipped, so
+    /// the frame is left-handed (its determinant is -1).
+    ///
+    /// Fails with `ZeroLength` for a degenerate triangle and `Singular` for
+    /// degenerate UVs, each to within rounding.
+    pub fn tangent_frame(p: &[Point3<F>; 3], uv: &[Point2<F>; 3]) -> Result<Mat3<F>, MathError> {
+        let (e1, e2) = (p[1] - p[0], p[2] - p[0]);
+        let (d1, d2) = (uv[1] - uv[0], uv[2] - uv[0]);
+        let det = d1.x * d2.y - d2.x * d1.y;
+        if det.abs() <= dependence_tolerance::<F>() * d1.magnitude() * d2.magnitude() {
+            return Err(MathError::Singular);
+        }
+        let tangent = (e1 * d2.y - e2 * d1.y) / det;
+        let bitangent = (e2 * d1.x - e1 * d2.x) / det;
+
+        let cross = e1.cross(e2);
+        if cross.magnitude() <= dependence_tolerance::<F>() * e1.magnitude() * e2.magnitude() {
+            return Err(MathError::ZeroLength);
+        }
+        let n = *Direction3::try_from_vec(cross)?;
+        let t = *Direction3::try_from_vec(tangent - n * n.dot(tangent))?;
+        let mut b = n.cross(t);
+        if b.dot(bitangent) < F::zero() {
+            b = -b;
+        }
+        Ok(Mat3::from_cols(t, b, n))
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Convert between f32 and f64
 
@@ -1043,10 +1692,76 @@ impl<F: FullFloat> Mat3<F> {
     }
 }
 
+// ----------------------------------------------------------------------------
+// Display / FromStr
+//
+// Matrices are written row-major, as "[[r0c0, r0c1], [r1c0, r1c1]]" (or with
+// one row per line under "{:#}"), matching the order of the new() parameters.
+
+impl<F: FullFloat + fmt::Display> fmt::Display for Mat2<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_matrix(f, &[ &[self.x.x, self.y.x],
+                           &[self.x.y, self.y.y] ])
+    }
+}
+
+impl<F: FullFloat + fmt::Display> fmt::Display for Mat3<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_matrix(f, &[ &[self.x.x, self.y.x, self.z.x],
+                           &[self.x.y, self.y.y, self.z.y],
+                           &[self.x.z, self.y.z, self.z.z] ])
+    }
+}
+
+impl<F: FullFloat + fmt::Display> fmt::Display for Mat4<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_matrix(f, &[ &[self.x.x, self.y.x, self.z.x, self.p.x],
+                           &[self.x.y, self.y.y, self.z.y, self.p.y],
+                           &[self.x.z, self.y.z, self.z.z, self.p.z],
+                           &[self.x.w, self.y.w, self.z.w, self.p.w] ])
+    }
+}
+
+/// Parses the elements in row-major order; brackets are optional
+impl<F: FullFloat> FromStr for Mat2<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Mat2<F>, ParseError> {
+        let e = parse_floats::<F>(s, 4)?;
+        Ok(Mat2::new(e[0], e[1],
+                     e[2], e[3]))
+    }
+}
+
+/// Parses the elements in row-major order; brackets are optional
+impl<F: FullFloat> FromStr for Mat3<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Mat3<F>, ParseError> {
+        let e = parse_floats::<F>(s, 9)?;
+        Ok(Mat3::new(e[0], e[1], e[2],
+                     e[3], e[4], e[5],
+                     e[6], e[7], e[8]))
+    }
+}
+
+/// Parses the elements in row-major order; brackets are optional
+impl<F: FullFloat> FromStr for Mat4<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Mat4<F>, ParseError> {
+        let e = parse_floats::<F>(s, 16)?;
+        Ok(Mat4::new(e[0], e[1], e[2], e[3],
+                     e[4], e[5], e[6], e[7],
+                     e[8], e[9], e[10], e[11],
+                     e[12], e[13], e[14], e[15]))
+    }
+}
+
 // ----------------------------------------------------------------------------
 // ApproxEq
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Mat2<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Mat2<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -1056,7 +1771,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Mat2<
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Mat3<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Mat3<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -1067,7 +1782,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Mat3<
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Mat4<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Mat4<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -1084,7 +1799,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Mat4<
 #[cfg(test)]
 mod tests {
     use super::{Mat2, Mat3, Mat4, Angle};
-    use super::super::vector::{Vec2, Vec3, Vec4, Direction3};
+    use super::super::vector::{Vec2, Vec3, Vec4, Direction2, Direction3, Point2, Point3};
 
     #[test]
     fn test_index() {
@@ -1123,6 +1838,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::op_ref)]
     fn test_mul_mat() {
         let left: Mat2<f32> = Mat2::new(1.0, 2.0, 3.0, 4.0);
         let right: Mat2<f32> = Mat2::new(6.0, 7.0, 8.0, 9.0);
@@ -1175,6 +1891,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::op_ref)]
     fn test_mul_vec() {
         let left: Mat2<f32> = Mat2::new(1.0, 2.0,
                                         3.0, 4.0);
@@ -1216,6 +1933,131 @@ mod tests {
         assert_eq!(product[3], 120.0);
     }
 
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_operators() {
+        let a = Mat3::new(1.0_f64, 2.0, 3.0,
+                          4.0, 5.0, 6.0,
+                          7.0, 8.0, 10.0);
+        let b = Mat3::new(0.5_f64, -1.0, 0.0,
+                          2.0, 1.0, 3.0,
+                          -1.0, 0.0, 1.0);
+        let sum = &a + &b;
+        let difference = &a - &b;
+        let product = &a * &b;
+        assert_eq!(a + b, sum);
+        assert_eq!(&a + b, sum);
+        assert_eq!(a + &b, sum);
+        assert_eq!(a - b, difference);
+        assert_eq!(&difference + &b, a);
+        assert_eq!(a * b, product);
+        assert_eq!(&a * b, product);
+        assert_eq!(a * &b, product);
+        assert_eq!(-a, &a * -1.0);
+        assert_eq!(-&a + a, Mat3::zero());
+        assert_eq!(a * 2.0 / 2.0, a);
+
+        let mut m = a;
+        m += b;
+        assert_eq!(m, sum);
+        m -= &b;
+        assert_eq!(m, a);
+        m *= b;
+        assert_eq!(m, product);
+        m = a;
+        m *= 3.0;
+        m /= 3.0;
+        assert_eq!(m, a);
+
+        // every element takes part
+        let a4 = Mat4::new(1.0_f64, 2.0, 3.0, 4.0,
+                           5.0, 6.0, 7.0, 8.0,
+                           9.0, 10.0, 11.0, 12.0,
+                           13.0, 14.0, 15.0, 16.0);
+        assert_eq!(a4 + a4, a4 * 2.0);
+        assert_eq!(a4 - a4, Mat4::zero());
+        let mut m4 = Mat4::identity();
+        m4 *= &a4;
+        assert_eq!(m4, a4);
+        let a2 = Mat2::new(1.0_f64, 2.0, 3.0, 4.0);
+        assert_eq!(a2 + a2, a2 * 2.0);
+        assert_eq!(-a2 - a2, a2 * -2.0);
+
+        let v = Vec3::new(1.0, -1.0, 2.0);
+        assert_eq!(a * v, &a * &v);
+        assert_eq!(v * a, &v * &a);
+        assert_eq!(a2 * Vec2::new(1.0, 1.0), Vec2::new(3.0, 7.0));
+        assert_eq!(Vec2::new(1.0, 1.0) * a2, Vec2::new(4.0, 6.0));
+        assert_eq!(a2 * Mat2::new(6.0, 7.0, 8.0, 9.0), Mat2::new(22.0, 25.0,
+                                                                 50.0, 57.0));
+        assert_eq!(a4 * Vec4::new(1.0, 2.0, 3.0, 4.0), Vec4::new(30.0, 70.0, 110.0, 150.0));
+        assert_eq!(Vec4::new(1.0, 2.0, 3.0, 4.0) * a4, Vec4::new(90.0, 100.0, 110.0, 120.0));
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
+    fn test_mul_point_direction() {
+        use float_cmp::ApproxEq;
+
+        let m = Mat3::new(2.0_f64, 0.0, 0.0,
+                          0.0, 1.0, 0.0,
+                          0.0, 0.0, 1.0);
+        assert_eq!(m * Point3::new(1.0, 2.0, 3.0), Point3::new(2.0, 2.0, 3.0));
+        let d: Direction3<f64> = From::from(Vec3::new(1.0, 1.0, 0.0));
+        let expected: Direction3<f64> = From::from(Vec3::new(2.0, 1.0, 0.0));
+        assert!((m * d).approx_eq(&expected, (1e-12, 8)));
+        assert!((&m * &d).approx_eq(&expected, (1e-12, 8)));
+
+        let m4 = Mat4::from_mat3(m, Point3::new(10.0, 0.0, 0.0));
+        assert_eq!(m4 * Point3::new(1.0, 2.0, 3.0), Point3::new(12.0, 2.0, 3.0));
+        assert!((m4 * d).approx_eq(&expected, (1e-12, 8)));
+
+        let m2 = Mat2::new(0.0_f64, -1.0, 1.0, 0.0);
+        assert_eq!(m2 * Point2::new(1.0, 2.0), Point2::new(-2.0, 1.0));
+        let d2 = Direction2::new_isnormal(1.0, 0.0);
+        assert_eq!(*(m2 * d2), Vec2::new(0.0, 1.0));
+
+        // a projection can take a direction to zero
+        let flat = Mat3::scale(&Vec3::new(1.0_f64, 1.0, 0.0));
+        let up = Direction3::new_isnormal(0.0, 0.0, 1.0);
+        assert!((flat * up).x.is_nan());
+        assert_eq!(Direction3::try_from_vec(flat * *up), Err(crate::MathError::ZeroLength));
+    }
+
+    #[test]
+    fn test_transform_point_direction() {
+        use float_cmp::ApproxEq;
+        use super::super::vector::Point3;
+
+        // non-uniform scale, then rotate 90 degrees about Z, then translate
+        let r = Mat3::<f64>::from_angle_z(Angle::from_degrees(90.0));
+        let s = Mat3::scale(&Vec3::new(2.0, 1.0, 1.0));
+        let linear = r * s;
+        let m = Mat4::from_mat3(linear, Point3::new(10.0, 0.0, 0.0));
+
+        let p = m.transform_point(&Point3::new(1.0, 1.0, 1.0));
+        assert!(p.approx_eq(&Point3::new(9.0, 2.0, 1.0), (1e-12, 8)));
+        let v = m.transform_vector(&Vec3::new(1.0, 1.0, 1.0));
+        assert!(v.approx_eq(&Vec3::new(-1.0, 2.0, 1.0), (1e-12, 8)));
+
+        // a tangent along the 45 degree line x = y becomes (-1, 2) normalized
+        let tangent: Direction3<f64> = From::from(Vec3::new(1.0, 1.0, 0.0));
+        let t = m.transform_direction(&tangent);
+        assert!(t.approx_eq(&From::from(Vec3::new(-1.0, 2.0, 0.0)), (1e-12, 8)));
+
+        // the normal of that line must stay perpendicular to the tangent,
+        // which transforming it as a direction would not do
+        let normal: Direction3<f64> = From::from(Vec3::new(1.0, -1.0, 0.0));
+        let wrong = m.transform_direction(&normal);
+        assert!(wrong.dot(t).abs() > 0.1);
+        let n: Direction3<f64> = From::from(linear.normal_matrix().unwrap() * *normal);
+        assert!((*n).dot(*t).abs() < 1e-12);
+
+        // for a pure rotation the normal matrix is the rotation itself
+        assert!(r.normal_matrix().unwrap().approx_eq(&r, (1e-12, 8)));
+        assert!(Mat3::<f64>::scale(&Vec3::new(1.0, 0.0, 1.0)).normal_matrix().is_none());
+    }
+
     #[test]
     fn test_inverse() {
         assert_eq!(Mat2::<f64>::identity().inverse().unwrap(), Mat2::<f64>::identity());
@@ -1246,6 +2088,271 @@ mod tests {
     }
 
     #[test]
+    fn test_orthonormalize() {
+        use float_cmp::ApproxEq;
+        use crate::MathError;
+        use super::super::vector::Point3;
+
+        // Accumulate a small rotation many times in f32, which drifts
+        let axis: Direction3<f32> = From::from(Vec3::new(1.0, 2.0, 3.0));
+        let step = Mat3::rotate_axis_angle(axis, Angle::from_degrees(0.7));
+        let mut m = Mat3::<f32>::identity();
+        for _ in 0..100_000 {
+            m *= step;
+        }
+        let error = |m: &Mat3<f32>| {
+            let mut t = *m;
+            t.transpose();
+            let d = (t * m) + (Mat3::identity() * -1.0);
+            [d.x, d.y, d.z].iter().map(|c| c.magnitude()).fold(0.0, f32::max)
+        };
+        assert!(error(&m) > 1e-5);
+        let before = m;
+        m.orthonormalize().unwrap();
+        assert!(error(&m) < 1e-6);
+        assert!((m.determinant() - 1.0).abs() < 1e-6);
+        assert!(m.approx_eq(&before, (1e-3, 8)));
+
+        // a reflection stays a reflection
+        let mut r = Mat3::new(1.0_f64, 0.1, 0.0,
+                              0.0, 1.0, 0.0,
+                              0.0, 0.0, -1.0);
+        r.orthonormalize().unwrap();
+        assert!((r.determinant() + 1.0).abs() < 1e-12);
+
+        let mut flat = Mat3::new(1.0_f64, 2.0, 0.0,
+                                 0.0, 0.0, 0.0,
+                                 0.0, 0.0, 1.0);
+        let copy = flat;
+        assert_eq!(flat.orthonormalize(), Err(MathError::Singular));
+        assert_eq!(flat, copy);
+
+        // parallel columns leave only rounding noise
+        let mut parallel = Mat3::new(0.1_f64, 0.3, 0.0,
+                                     0.1, 0.3, 0.0,
+                                     0.1, 0.3, 1.0);
+        assert_eq!(parallel.orthonormalize(), Err(MathError::Singular));
+        let mut parallel = Mat3::new(0.1_f32, 0.3, 0.0,
+                                     0.1, 0.3, 0.0,
+                                     0.1, 0.3, 1.0);
+        assert_eq!(parallel.orthonormalize(), Err(MathError::Singular));
+        let mut m4 = Mat4::from_mat3(parallel, Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(m4.orthonormalize_rotation(), Err(MathError::Singular));
+
+        // Mat4 keeps its translation and loses its scale
+        let rot = Mat3::<f64>::rotate_axis_angle(From::from(Vec3::new(0.0, 1.0, 1.0)),
+                                                 Angle::from_degrees(40.0));
+        let scaled = rot * Mat3::scale(&Vec3::new(2.0, 2.0, 2.0));
+        let mut m4 = Mat4::from_mat3(scaled, Point3::new(1.0, 2.0, 3.0));
+        m4.orthonormalize_rotation().unwrap();
+        assert!(m4.approx_eq(&Mat4::from_mat3(rot, Point3::new(1.0, 2.0, 3.0)), (1e-12, 8)));
+    }
+
+    #[test]
+    fn test_tangent_frame() {
+        use float_cmp::ApproxEq;
+        use crate::MathError;
+        use super::super::vector::{Point2, Point3};
+
+        let p = [Point3::new(0.0_f64, 0.0, 0.0), Point3::new(2.0, 0.0, 0.0),
+                 Point3::new(0.0, 2.0, 0.0)];
+        let uv = [Point2::new(0.0_f64, 0.0), Point2::new(1.0, 0.0), Point2::new(0.0, 1.0)];
+        let tbn = Mat3::tangent_frame(&p, &uv).unwrap();
+        assert!(tbn.approx_eq(&Mat3::identity(), (1e-12, 8)));
+
+        // U running along Y and V along X: mirrored
+        let uv = [Point2::new(0.0_f64, 0.0), Point2::new(0.0, 1.0), Point2::new(1.0, 0.0)];
+        let tbn = Mat3::tangent_frame(&p, &uv).unwrap();
+        assert!(tbn.x.approx_eq(&Vec3::new(0.0, 1.0, 0.0), (1e-12, 8)));
+        assert!(tbn.y.approx_eq(&Vec3::new(1.0, 0.0, 0.0), (1e-12, 8)));
+        assert!((tbn.determinant() + 1.0).abs() < 1e-12);
+
+        // a sheared UV layout still gives an orthonormal frame with the
+        // tangent along increasing U
+        let p = [Point3::new(1.0_f64, 0.0, 0.0), Point3::new(1.0, 3.0, 1.0),
+                 Point3::new(1.0, -1.0, 4.0)];
+        let uv = [Point2::new(0.2_f64, 0.1), Point2::new(0.9, 0.3), Point2::new(0.4, 0.8)];
+        let tbn = Mat3::tangent_frame(&p, &uv).unwrap();
+        let mut t = tbn;
+        t.transpose();
+        assert!((t * tbn).approx_eq(&Mat3::identity(), (1e-12, 8)));
+        assert!(tbn.z.approx_eq(&Vec3::new(1.0, 0.0, 0.0), (1e-12, 8)));
+
+        let same = [Point2::new(0.5_f64, 0.5); 3];
+        assert_eq!(Mat3::tangent_frame(&p, &same), Err(MathError::Singular));
+        let line = [Point3::new(0.0_f64, 0.0, 0.0), Point3::new(1.0, 1.0, 1.0),
+                    Point3::new(2.0, 2.0, 2.0)];
+        assert_eq!(Mat3::tangent_frame(&line, &uv), Err(MathError::ZeroLength));
+        // collinear to within rounding
+        let line = [Point3::new(0.0_f64, 0.0, 0.0), Point3::new(0.1, 0.2, 0.3),
+                    Point3::new(0.3, 0.6, 0.9)];
+        assert_eq!(Mat3::tangent_frame(&line, &uv), Err(MathError::ZeroLength));
+        let uv_line = [Point2::new(0.0_f64, 0.0), Point2::new(0.7, 0.1), Point2::new(2.1, 0.3)];
+        assert_eq!(Mat3::tangent_frame(&p, &uv_line), Err(MathError::Singular));
+    }
+
+    #[test]
+    fn test_try_inverse() {
+        use crate::MathError;
+
+        let m = Mat2::new(1.0_f64, 2.0, 3.0, 4.0);
+        assert_eq!(m.try_inverse(), Ok(m.inverse().unwrap()));
+        assert_eq!(Mat2::new(1.0_f64, 2.0, 2.0, 4.0).try_inverse(), Err(MathError::Singular));
+        assert_eq!(Mat2::new(f64::NAN, 0.0, 0.0, 1.0).try_inverse(),
+                   Err(MathError::NonFinite));
+
+        assert_eq!(Mat3::<f64>::zero().try_inverse(), Err(MathError::Singular));
+        let mut m = Mat3::<f64>::identity();
+        m[(1,2)] = f64::INFINITY;
+        assert_eq!(m.try_inverse(), Err(MathError::NonFinite));
+
+        assert_eq!(Mat4::<f64>::identity().try_inverse(), Ok(Mat4::identity()));
+        let flat = Mat4::scale(&Vec4::new(1.0_f64, 1.0, 0.0, 1.0));
+        assert_eq!(flat.try_inverse(), Err(MathError::Singular));
+        let mut m = Mat4::<f64>::identity();
+        m[(3,0)] = f64::NAN;
+        assert_eq!(m.try_inverse(), Err(MathError::NonFinite));
+
+        // the determinant overflows f32, but the matrix is fine
+        let big = Mat3::new(1e20_f32, 0.0, 0.0,
+                            0.0, 1e20, 0.0,
+                            0.0, 0.0, 1e20);
+        assert_eq!(big.try_inverse(), Ok(Mat3::new(1e-20, 0.0, 0.0,
+                                                   0.0, 1e-20, 0.0,
+                                                   0.0, 0.0, 1e-20)));
+        let small = Mat2::new(0.0_f32, 1e-30, -1e-30, 0.0);
+        assert_eq!(small.try_inverse(), Ok(Mat2::new(0.0, -1e30, 1e30, 0.0)));
+    }
+
+    #[test]
+    fn test_adjugate() {
+        use float_cmp::ApproxEq;
+
+        let m = Mat2::new(1.0_f64, 2.0, 3.0, 4.0);
+        assert_eq!(m.adjugate(), Mat2::new(4.0, -2.0, -3.0, 1.0));
+        assert_eq!(Mat2::new(1.0_f64, 2.0, 2.0, 4.0).adjugate(), Mat2::new(4.0, -2.0, -2.0, 1.0));
+
+        let m = Mat3::new(2.0_f64, 0.0, 1.0,
+                          1.0, 3.0, 0.0,
+                          0.0, 1.0, 4.0);
+        assert_eq!(m.cofactor()[(0,0)], 12.0);
+        assert_eq!(m.cofactor()[(1,2)], -2.0);
+        let expected = m.inverse().unwrap() * m.determinant();
+        assert!(m.adjugate().approx_eq(&expected, (1e-12, 8)));
+
+        let m = Mat4::new(2.0_f64, 0.0, 1.0, 3.0,
+                          1.0, 3.0, 0.0, -1.0,
+                          0.0, 1.0, 4.0, 2.0,
+                          1.0, 0.0, 0.0, 5.0);
+        let expected = m.inverse().unwrap() * m.determinant();
+        assert!(m.adjugate().approx_eq(&expected, (1e-12, 8)));
+        // A · adj(A) = det(A) I, singular or not
+        let flat = Mat4::scale(&Vec4::new(1.0_f64, 2.0, 0.0, 1.0));
+        assert_eq!(flat * flat.adjugate(), Mat4::zero());
+    }
+
+    #[test]
+    fn test_predicates() {
+        let axis: Direction3<f64> = From::from(Vec3::new(1.0, 2.0, -0.5));
+        let r = Mat3::rotate_axis_angle(axis, Angle::from_degrees(70.0));
+        assert!(r.is_orthogonal(1e-12));
+        assert!(r.is_rotation(1e-12));
+        let mirror = r * Mat3::scale(&Vec3::new(1.0, -1.0, 1.0));
+        assert!(mirror.is_orthogonal(1e-12));
+        assert!(!mirror.is_rotation(1e-12));
+        let scaled = &r * 1.001;
+        assert!(!scaled.is_orthogonal(1e-6));
+        assert!(scaled.is_orthogonal(1e-2));
+        assert!(Mat2::<f64>::from_angle(Angle::from_degrees(30.0)).is_rotation(1e-12));
+
+        let mut m = Mat4::from_mat3(r, Point3::new(1.0, 2.0, 3.0));
+        assert!(m.is_affine());
+        assert!(!m.is_rotation(1e-12));
+        m.set_translation(Point3::new(0.0, 0.0, 0.0));
+        assert!(m.is_rotation(1e-12));
+        m[(3,2)] = 0.5;
+        assert!(!m.is_affine());
+
+        assert!(Mat3::<f64>::identity().is_invertible(1e-9));
+        assert!(!Mat3::scale(&Vec3::new(1.0_f64, 1.0, 1e-12)).is_invertible(1e-9));
+        assert!(!Mat3::new(1.0_f64, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0).is_invertible(1e-9));
+        assert!(!Mat3::new(f64::NAN, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0)
+                .is_orthogonal(1e-9));
+    }
+
+    #[test]
+    fn test_is_diagonal() {
+        assert!(Mat2::<f64>::identity().is_diagonal());
+        assert!(Mat3::<f64>::identity().is_diagonal());
+        assert!(Mat4::<f64>::identity().is_diagonal());
+        assert!(Mat4::scale(&Vec4::new(1.0_f64, 2.0, 3.0, 4.0)).is_diagonal());
+        // every off-diagonal element counts
+        for row in 0..4 {
+            for col in 0..4 {
+                if row == col { continue; }
+                let mut d = Mat4::<f64>::identity();
+                d[(row, col)] = 1.0;
+                assert!(!d.is_diagonal());
+                if row < 3 && col < 3 {
+                    let mut d = Mat3::<f64>::identity();
+                    d[(row, col)] = 1.0;
+                    assert!(!d.is_diagonal());
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_mat4_add() {
+        let mut a = Mat4::<f64>::zero();
+        let mut b = Mat4::<f64>::zero();
+        for row in 0..4 {
+            for col in 0..4 {
+                a[(row, col)] = (row * 4 + col) as f64;
+                b[(row, col)] = 100.0 * (row * 4 + col) as f64;
+            }
+        }
+        let sum = a + b;
+        for row in 0..4 {
+            for col in 0..4 {
+                assert_eq!(sum[(row, col)], 101.0 * (row * 4 + col) as f64);
+            }
+        }
+    }
+
+    #[test]
+    fn test_norms() {
+        let m = Mat2::new(1.0_f64, -2.0, 3.0, 4.0);
+        assert_eq!(m.trace(), 5.0);
+        assert_eq!(m.norm_1(), 6.0);
+        assert_eq!(m.norm_inf(), 7.0);
+        assert_eq!(m.frobenius_norm(), 30.0_f64.sqrt());
+
+        let m = Mat4::new(1.0_f64, 2.0, 3.0, 4.0,
+                          0.0, -6.0, 0.0, 0.0,
+                          0.0, 0.0, 2.0, 0.0,
+                          0.0, 0.0, 0.0, 1.0);
+        assert_eq!(m.trace(), -2.0);
+        assert_eq!(m.norm_1(), 8.0);
+        assert_eq!(m.norm_inf(), 10.0);
+        assert_eq!(Mat4::<f64>::identity().frobenius_norm(), 2.0);
+
+        let axis: Direction3<f64> = From::from(Vec3::new(0.0, 1.0, 0.0));
+        let r = Mat3::rotate_axis_angle(axis, Angle::from_degrees(90.0));
+        assert!((r.condition_number() - 1.0).abs() < 1e-12);
+        let r = Mat2::from_angle(Angle::from_degrees(45.0_f64));
+        assert!((r.condition_number() - 2.0).abs() < 1e-12);
+        let axis: Direction3<f64> = From::from(Vec3::new(1.0, 2.0, 3.0));
+        let r = Mat3::rotate_axis_angle(axis, Angle::from_degrees(70.0));
+        let k = r.condition_number();
+        assert!(k > 1.0 + 1e-3 && k <= 3.0);
+        let s = Mat3::scale(&Vec3::new(1.0_f64, 1.0, 1e-6));
+        assert!((s.condition_number() - 1e6).abs() < 1e-3);
+        assert_eq!(Mat3::<f64>::zero().condition_number(), f64::INFINITY);
+    }
+
+    #[test]
+    #[allow(clippy::op_ref)]
     fn test_axis_angle() {
         let axis: Direction3<f32> = From::from(Vec3::new(1.0, 0.0, 0.0));
         let angle = Angle::new_radians(::std::f32::consts::FRAC_PI_4);
@@ -1269,4 +2376,31 @@ mod tests {
             0.0, s,   c,   c*10.0,
             0.0, 0.0, 0.0, 1.0));
     }
+
+    #[test]
+    fn test_display() {
+        let m: Mat2<f32> = Mat2::new(1.0, 2.0,
+                                     3.0, 4.0);
+        assert_eq!(format!("{}", m), "[[1, 2], [3, 4]]");
+        assert_eq!(format!("{:#.1}", m), "[[1.0, 2.0],\n [3.0, 4.0]]");
+
+        let m: Mat3<f64> = Mat3::new(1.0, 2.0, 3.0,
+                                     4.0, 5.0, 6.0,
+                                     7.0, 8.0, 9.0);
+        assert_eq!(format!("{}", m), "[[1, 2, 3], [4, 5, 6], [7, 8, 9]]");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let m: Mat4<f32> = Mat4::new(1.0, 2.0, 3.0, 4.0,
+                                     5.0, 6.0, 7.0, 8.0,
+                                     9.0, 10.0, 11.0, 12.0,
+                                     13.0, 14.0, 15.0, 16.5);
+        assert_eq!(format!("{}", m).parse::<Mat4<f32>>(), Ok(m));
+        assert_eq!(format!("{:#}", m).parse::<Mat4<f32>>(), Ok(m));
+        assert_eq!("1 2 3 4".parse::<Mat2<f32>>(), Ok(Mat2::new(1.0, 2.0, 3.0, 4.0)));
+        assert_eq!("1 2 3 4 5 6 7 8 9".parse::<Mat3<f32>>(),
+                   Ok(Mat3::new(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0)));
+        assert!("1 2 3".parse::<Mat2<f32>>().is_err());
+    }
 }
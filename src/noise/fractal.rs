@@ -0,0 +1,158 @@
+
+use std::ops::{Add, Mul};
+use crate::FullFloat;
+use super::{Noise, constant};
+
+/// Fractional Brownian motion: a sum of octaves of a source noise at
+/// increasing frequency and decreasing amplitude.
+///
+/// The sum is divided by the total amplitude, so the result stays within the
+/// range of the source noise.
+#[derive(Debug, Clone)]
+pub struct Fbm<N, F> {
+    pub source: N,
+    pub octaves: u32,
+    /// Frequency multiplier between octaves
+    pub lacunarity: F,
+    /// Amplitude multiplier between octaves
+    pub gain: F,
+}
+
+impl<N, F: FullFloat> Fbm<N, F> {
+    /// Six octaves, lacunarity 2, gain 0.5
+    pub fn new(source: N) -> Fbm<N, F> {
+        Fbm {
+            source,
+            octaves: 6,
+            lacunarity: constant(2.0),
+            gain: constant(0.5),
+        }
+    }
+}
+
+impl<N, F, V> Noise<F, V> for Fbm<N, F>
+    where N: Noise<F, V>,
+          F: FullFloat,
+          V: Copy + Add<Output = V> + Mul<F, Output = V>
+{
+    fn sample(&self, p: V) -> (F, V) {
+        let mut value = F::zero();
+        let mut grad = p * F::zero();
+        let mut total = F::zero();
+        let mut freq = F::one();
+        let mut amp = F::one();
+        for _ in 0..self.octaves {
+            let (v, g) = self.source.sample(p * freq);
+            value += v * amp;
+            grad = grad + g * (amp * freq);
+            total += amp;
+            freq *= self.lacunarity;
+            amp *= self.gain;
+        }
+        if total > F::zero() {
+            (value / total, grad * (F::one() / total))
+        } else {
+            (value, grad)
+        }
+    }
+}
+
+/// Ridged fractal noise: like `Fbm`, but each octave is folded as
+/// `(1 - |n|)^2`, producing sharp ridges where the source crosses zero.
+///
+/// For a source within -1 to 1 the result is within 0 to 1.
+#[derive(Debug, Clone)]
+pub struct Ridged<N, F> {
+    pub source: N,
+    pub octaves: u32,
+    /// Frequency multiplier between octaves
+    pub lacunarity: F,
+    /// Amplitude multiplier between octaves
+    pub gain: F,
+}
+
+impl<N, F: FullFloat> Ridged<N, F> {
+    /// Six octaves, lacunarity 2, gain 0.5
+    pub fn new(source: N) -> Ridged<N, F> {
+        Ridged {
+            source,
+            octaves: 6,
+            lacunarity: constant(2.0),
+            gain: constant(0.5),
+        }
+    }
+}
+
+impl<N, F, V> Noise<F, V> for Ridged<N, F>
+    where N: Noise<F, V>,
+          F: FullFloat,
+          V: Copy + Add<Output = V> + Mul<F, Output = V>
+{
+    fn sample(&self, p: V) -> (F, V) {
+        let two: F = constant(2.0);
+        let mut value = F::zero();
+        let mut grad = p * F::zero();
+        let mut total = F::zero();
+        let mut freq = F::one();
+        let mut amp = F::one();
+        for _ in 0..self.octaves {
+            let (v, g) = self.source.sample(p * freq);
+            let ridge = F::one() - v.abs();
+            value += ridge * ridge * amp;
+            // d/dp (1 - |v|)^2 = -2 (1 - |v|) sign(v) dv/dp
+            let sign = if v < F::zero() { -F::one() } else { F::one() };
+            grad = grad + g * (-two * ridge * sign * amp * freq);
+            total += amp;
+            freq *= self.lacunarity;
+            amp *= self.gain;
+        }
+        if total > F::zero() {
+            (value / total, grad * (F::one() / total))
+        } else {
+            (value, grad)
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec2, Vec3};
+    use crate::noise::{Noise, Perlin, Simplex};
+    use crate::noise::tests::{check_gradient, range};
+    use super::{Fbm, Ridged};
+
+    #[test]
+    fn test_fbm_single_octave() {
+        let mut fbm = Fbm::new(Perlin::new(1));
+        fbm.octaves = 1;
+        let p = Vec3::new(0.3_f64, 1.7, -2.2);
+        assert_eq!(fbm.sample(p), Perlin::new(1).sample(p));
+    }
+
+    #[test]
+    fn test_fbm_gradient() {
+        let mut fbm = Fbm::new(Perlin::new(2));
+        fbm.octaves = 4;
+        check_gradient(&fbm, 1e-4);
+    }
+
+    #[test]
+    fn test_ridged_gradient() {
+        let mut ridged = Ridged::new(Simplex::new(2));
+        ridged.octaves = 4;
+        check_gradient(&ridged, 1e-4);
+    }
+
+    #[test]
+    fn test_range() {
+        let fbm = Fbm::new(Simplex::new(5));
+        let (lo, hi) = range(&fbm, |t| Vec2::new(t * 0.0731, t * 0.0419));
+        assert!(lo >= -1.0 && hi <= 1.0);
+
+        let ridged = Ridged::new(Perlin::new(5));
+        let (lo, hi) = range(&ridged, |t| Vec2::new(t * 0.0731, t * 0.0419));
+        assert!(lo >= 0.0 && hi <= 1.0);
+    }
+}
@@ -0,0 +1,219 @@
+// Plumbing between the vector types and the internal array form.  These
+// must be defined before the submodules that use them.
+macro_rules! impl_noise {
+    ($Noise:ident, $VecN:ident { $($field:ident: $i:expr),* }, $n:expr) => {
+        impl<F: FullFloat> $crate::noise::Noise<F, $VecN<F>> for $Noise {
+            #[inline]
+            fn sample(&self, p: $VecN<F>) -> (F, $VecN<F>) {
+                let (value, g) = self.eval::<F, $n>([$(p.$field),*]);
+                (value, $VecN { $($field: g[$i]),* })
+            }
+        }
+    }
+}
+
+macro_rules! impl_noise_all {
+    ($Noise:ident) => {
+        impl_noise!($Noise, Vec2 { x: 0, y: 1 }, 2);
+        impl_noise!($Noise, Vec3 { x: 0, y: 1, z: 2 }, 3);
+        impl_noise!($Noise, Vec4 { x: 0, y: 1, z: 2, w: 3 }, 4);
+    }
+}
+
+pub mod perlin;
+pub use self::perlin::Perlin;
+
+pub mod simplex;
+pub use self::simplex::Simplex;
+
+pub mod worley;
+pub use self::worley::Worley;
+
+pub mod fractal;
+pub use self::fractal::{Fbm, Ridged};
+
+use num_traits::NumCast;
+use crate::FullFloat;
+
+// NOTE: Every noise function here is built only from integer hashing and
+// IEEE-754 basic arithmetic (+, -, *, /, sqrt, floor), all of which are
+// exactly specified.  No transcendental functions or platform RNGs are used,
+// so the same seed and the same input yield bit-identical results on every
+// platform (for a given float type).
+
+/// A coherent noise function, sampled at a position of type V.
+pub trait Noise<F, V> {
+    /// Evaluate the noise at `p`, yielding the value and its analytic gradient
+    fn sample(&self, p: V) -> (F, V);
+}
+
+// ----------------------------------------------------------------------------
+// Integer hashing
+
+// Sebastiano Vigna's splitmix64 finalizer
+#[inline]
+fn splitmix64(mut z: u64) -> u64 {
+    z = z.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+// Hash a seed together with integer lattice coordinates
+#[inline]
+fn hash_coords<const N: usize>(seed: u64, c: [i64; N]) -> u64 {
+    let mut h = splitmix64(seed);
+    for k in c.iter() {
+        h = splitmix64(h ^ (*k as u64));
+    }
+    h
+}
+
+// A seeded permutation of 0..256, as in Ken Perlin's reference implementation
+#[derive(Debug, Clone)]
+struct PermutationTable {
+    perm: [u8; 256],
+}
+
+impl PermutationTable {
+    fn new(seed: u64) -> PermutationTable {
+        let mut perm = [0_u8; 256];
+        for (i, p) in perm.iter_mut().enumerate() {
+            *p = i as u8;
+        }
+        // Fisher-Yates shuffle
+        let mut state = seed;
+        for i in (1..256).rev() {
+            state = splitmix64(state);
+            let j = (state % (i as u64 + 1)) as usize;
+            perm.swap(i, j);
+        }
+        PermutationTable { perm }
+    }
+
+    #[inline]
+    fn hash<const N: usize>(&self, c: [i64; N]) -> u8 {
+        let mut h: u8 = 0;
+        for k in c.iter() {
+            h = self.perm[((h as i64).wrapping_add(*k) & 255) as usize];
+        }
+        h
+    }
+}
+
+// A gradient with each component +1 or -1, chosen by the bits of the hash
+#[inline]
+fn gradient<F: FullFloat, const N: usize>(h: u8) -> [F; N] {
+    let mut g = [F::one(); N];
+    for (k, gk) in g.iter_mut().enumerate() {
+        if (h >> k) & 1 == 1 {
+            *gk = -F::one();
+        }
+    }
+    g
+}
+
+#[inline]
+fn dot<F: FullFloat, const N: usize>(a: &[F; N], b: &[F; N]) -> F {
+    let mut sum = F::zero();
+    for k in 0..N {
+        sum += a[k] * b[k];
+    }
+    sum
+}
+
+// Split a coordinate into its lattice cell and the offset within the cell
+#[inline]
+fn lattice<F: FullFloat>(x: F) -> (i64, F) {
+    let fl = x.floor();
+    (fl.to_i64().unwrap_or(0), x - fl)
+}
+
+#[inline]
+fn constant<F: FullFloat>(x: f64) -> F {
+    NumCast::from(x).unwrap()
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::{Vec2, Vec3, Vec4};
+    use super::Noise;
+
+    // Compare the analytic gradient against central differences
+    pub fn check_gradient<N>(noise: &N, tolerance: f64)
+        where N: Noise<f64, Vec2<f64>> + Noise<f64, Vec3<f64>> + Noise<f64, Vec4<f64>>
+    {
+        let h = 1e-6;
+        for i in 0..200 {
+            let t = i as f64 * 0.731 + 0.013;
+            let p2 = Vec2::new(t * 1.37 - 40.0, t * -0.53 + 7.0);
+            let p3 = Vec3::new(t * 0.91 - 3.0, t * 1.13, t * -0.77 + 20.0);
+            let p4 = Vec4::new(t * -0.63, t * 0.87 + 1.0, t * 1.21 - 9.0, t * 0.45);
+
+            let (_, g) = noise.sample(p2);
+            for k in 0..2 {
+                let mut a = p2; a[k] += h;
+                let mut b = p2; b[k] -= h;
+                let fd = (noise.sample(a).0 - noise.sample(b).0) / (2.0 * h);
+                assert!((fd - g[k]).abs() < tolerance, "2D grad {} vs {} at {:?}", g[k], fd, p2);
+            }
+
+            let (_, g) = noise.sample(p3);
+            for k in 0..3 {
+                let mut a = p3; a[k] += h;
+                let mut b = p3; b[k] -= h;
+                let fd = (noise.sample(a).0 - noise.sample(b).0) / (2.0 * h);
+                assert!((fd - g[k]).abs() < tolerance, "3D grad {} vs {} at {:?}", g[k], fd, p3);
+            }
+
+            let (_, g) = noise.sample(p4);
+            for k in 0..4 {
+                let mut a = p4; a[k] += h;
+                let mut b = p4; b[k] -= h;
+                let fd = (noise.sample(a).0 - noise.sample(b).0) / (2.0 * h);
+                assert!((fd - g[k]).abs() < tolerance, "4D grad {} vs {} at {:?}", g[k], fd, p4);
+            }
+        }
+    }
+
+    // Sample the noise densely and return the extreme values seen
+    pub fn range<N, V>(noise: &N, point: impl Fn(f64) -> V) -> (f64, f64)
+        where N: Noise<f64, V>
+    {
+        let mut lo = f64::MAX;
+        let mut hi = f64::MIN;
+        for i in 0..20000 {
+            let (v, _) = noise.sample(point(i as f64));
+            lo = lo.min(v);
+            hi = hi.max(v);
+        }
+        (lo, hi)
+    }
+
+    // These pin the exact output bits, so any change that would make a server
+    // and client disagree about the world shows up here.
+    #[test]
+    fn test_known_values() {
+        use super::{Perlin, Simplex, Worley};
+        let p = Vec3::new(1.3_f64, -7.25, 100.5);
+        assert_eq!(Perlin::new(1).sample(p).0.to_bits(), 4600103686379201854);
+        assert_eq!(Simplex::new(1).sample(p).0.to_bits(), 13827264594361463007);
+        assert_eq!(Worley::new(1).sample(p).0.to_bits(), 4599617206154181264);
+    }
+
+    #[test]
+    fn test_permutation_table() {
+        let a = super::PermutationTable::new(1);
+        let b = super::PermutationTable::new(1);
+        let c = super::PermutationTable::new(2);
+        assert_eq!(&a.perm[..], &b.perm[..]);
+        assert!(a.perm[..] != c.perm[..]);
+        let mut seen = [false; 256];
+        for p in a.perm.iter() {
+            seen[*p as usize] = true;
+        }
+        assert!(seen.iter().all(|s| *s));
+    }
+}
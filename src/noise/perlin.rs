@@ -0,0 +1,139 @@
+
+use crate::{FullFloat, Vec2, Vec3, Vec4};
+use super::{PermutationTable, gradient, dot, lattice, constant};
+
+/// Seeded Perlin gradient noise ("improved" noise, with quintic fade).
+///
+/// Values are roughly within -1 to 1, and are exactly zero at every integer
+/// lattice point.
+#[derive(Debug, Clone)]
+pub struct Perlin {
+    perm: PermutationTable,
+}
+
+impl Perlin {
+    pub fn new(seed: u64) -> Perlin {
+        Perlin {
+            perm: PermutationTable::new(seed),
+        }
+    }
+
+    fn eval<F: FullFloat, const N: usize>(&self, p: [F; N]) -> (F, [F; N]) {
+        let one = F::one();
+        let six: F = constant(6.0);
+        let ten: F = constant(10.0);
+        let fifteen: F = constant(15.0);
+        let thirty: F = constant(30.0);
+
+        let mut cell = [0_i64; N];
+        let mut f = [F::zero(); N];
+        let mut u = [F::zero(); N];
+        let mut du = [F::zero(); N];
+        for k in 0..N {
+            let (c, t) = lattice(p[k]);
+            cell[k] = c;
+            f[k] = t;
+            // fade(t) = 6t^5 - 15t^4 + 10t^3, and its derivative
+            u[k] = t * t * t * (t * (t * six - fifteen) + ten);
+            du[k] = thirty * t * t * (t - one) * (t - one);
+        }
+
+        let mut value = F::zero();
+        let mut grad = [F::zero(); N];
+        for corner in 0..(1_usize << N) {
+            let mut c = cell;
+            let mut d = f;
+            for k in 0..N {
+                if (corner >> k) & 1 == 1 {
+                    c[k] = c[k].wrapping_add(1);
+                    d[k] -= one;
+                }
+            }
+            let g: [F; N] = gradient(self.perm.hash(c));
+            let v = dot(&g, &d);
+
+            // multilinear weight of this corner, and its partial derivatives
+            let mut w = one;
+            for (k, uk) in u.iter().enumerate() {
+                w *= if (corner >> k) & 1 == 1 { *uk } else { one - *uk };
+            }
+            value += w * v;
+            for k in 0..N {
+                let mut dw = if (corner >> k) & 1 == 1 { du[k] } else { -du[k] };
+                for (j, uj) in u.iter().enumerate() {
+                    if j != k {
+                        dw *= if (corner >> j) & 1 == 1 { *uj } else { one - *uj };
+                    }
+                }
+                grad[k] += w * g[k] + dw * v;
+            }
+        }
+
+        // With +/-1 gradients the extremes approach +/- N/2
+        let scale: F = constant(2.0 / N as f64);
+        for g in grad.iter_mut() {
+            *g *= scale;
+        }
+        (value * scale, grad)
+    }
+}
+
+impl_noise_all!(Perlin);
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec2, Vec3, Vec4};
+    use crate::noise::Noise;
+    use crate::noise::tests::{check_gradient, range};
+    use super::Perlin;
+
+    #[test]
+    fn test_deterministic() {
+        let a = Perlin::new(42);
+        let b = Perlin::new(42);
+        let c = Perlin::new(43);
+        let p = Vec3::new(1.3_f64, -7.25, 100.5);
+        assert_eq!(a.sample(p), b.sample(p));
+        assert!(a.sample(p).0 != c.sample(p).0);
+    }
+
+    #[test]
+    fn test_zero_on_lattice() {
+        let n = Perlin::new(7);
+        for i in -5..5 {
+            let (v, _) = n.sample(Vec2::new(i as f64, (i * 3) as f64));
+            assert_eq!(v, 0.0);
+            let (v, _) = n.sample(Vec3::new(i as f32, 2.0, -(i as f32)));
+            assert_eq!(v, 0.0);
+        }
+    }
+
+    #[test]
+    fn test_gradient() {
+        check_gradient(&Perlin::new(3), 1e-5);
+    }
+
+    #[test]
+    fn test_range() {
+        let n = Perlin::new(11);
+        let (lo, hi) = range(&n, |t| Vec2::new(t * 0.0731, t * 0.0419));
+        assert!(lo >= -1.0 && hi <= 1.0);
+        assert!(lo < -0.3 && hi > 0.3);
+        let (lo, hi) = range(&n, |t| Vec3::new(t * 0.0731, t * 0.0419, t * 0.0173));
+        assert!(lo >= -1.0 && hi <= 1.0);
+        assert!(lo < -0.3 && hi > 0.3);
+        let (lo, hi) = range(&n, |t| Vec4::new(t * 0.0731, t * 0.0419, t * 0.0173, t * 0.0587));
+        assert!(lo >= -1.0 && hi <= 1.0);
+        assert!(lo < -0.3 && hi > 0.3);
+    }
+
+    #[test]
+    fn test_f32_matches_f64() {
+        let n = Perlin::new(5);
+        let (a, _) = n.sample(Vec3::new(0.25_f32, 1.5, -2.75));
+        let (b, _) = n.sample(Vec3::new(0.25_f64, 1.5, -2.75));
+        assert!((a as f64 - b).abs() < 1e-6);
+    }
+}
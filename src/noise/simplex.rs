@@ -0,0 +1,150 @@
+
+use crate::{FullFloat, Vec2, Vec3, Vec4};
+use super::{PermutationTable, gradient, dot, lattice, constant};
+
+/// Seeded gradient noise on a simplex lattice, in the style of OpenSimplex.
+///
+/// Each lattice vertex contributes through a radial falloff kernel
+/// `(0.5 - d^2)^4`, which reaches zero before the next simplex so the noise
+/// (and its gradient) are continuous everywhere.  Values are roughly within
+/// -1 to 1.
+#[derive(Debug, Clone)]
+pub struct Simplex {
+    perm: PermutationTable,
+}
+
+// Empirical normalization factors for 2, 3 and 4 dimensions
+const SCALE: [f64; 3] = [64.0, 56.0, 48.0];
+
+impl Simplex {
+    pub fn new(seed: u64) -> Simplex {
+        Simplex {
+            perm: PermutationTable::new(seed),
+        }
+    }
+
+    fn eval<F: FullFloat, const N: usize>(&self, p: [F; N]) -> (F, [F; N]) {
+        let one = F::one();
+        let n: F = constant(N as f64);
+        let half: F = constant(0.5);
+        let eight: F = constant(8.0);
+
+        // Skew the input onto the hypercubic lattice to find the cell
+        let root = (n + one).sqrt();
+        let skew = (root - one) / n;
+        let unskew = (one - one / root) / n;
+
+        let mut s = F::zero();
+        for x in p.iter() {
+            s += *x;
+        }
+        s *= skew;
+
+        let mut cell = [0_i64; N];
+        let mut origin = [F::zero(); N];
+        let mut origin_sum = F::zero();
+        for k in 0..N {
+            let (c, frac) = lattice(p[k] + s);
+            cell[k] = c;
+            origin[k] = p[k] + s - frac;
+            origin_sum += origin[k];
+        }
+
+        // Offset from the cell origin, in unskewed space
+        let t = origin_sum * unskew;
+        let mut x0 = [F::zero(); N];
+        for k in 0..N {
+            x0[k] = p[k] - (origin[k] - t);
+        }
+
+        // Rank the axes by offset; this picks which simplex within the
+        // hypercube contains the point (insertion sort, ties by axis)
+        let mut order = [0_usize; N];
+        for (k, o) in order.iter_mut().enumerate() {
+            *o = k;
+        }
+        for i in 1..N {
+            let mut j = i;
+            while j > 0 && x0[order[j]] > x0[order[j - 1]] {
+                order.swap(j, j - 1);
+                j -= 1;
+            }
+        }
+
+        let mut value = F::zero();
+        let mut grad = [F::zero(); N];
+        let mut c = cell;
+        let mut d = x0;
+        for vertex in 0..(N + 1) {
+            if vertex > 0 {
+                // step to the next vertex along the next-largest axis
+                let axis = order[vertex - 1];
+                c[axis] = c[axis].wrapping_add(1);
+                for dk in d.iter_mut() {
+                    *dk += unskew;
+                }
+                d[axis] -= one;
+            }
+
+            let falloff = half - dot(&d, &d);
+            if falloff <= F::zero() {
+                continue;
+            }
+            let g: [F; N] = gradient(self.perm.hash(c));
+            let gd = dot(&g, &d);
+            let t2 = falloff * falloff;
+            let t4 = t2 * t2;
+            value += t4 * gd;
+            for k in 0..N {
+                grad[k] += t4 * g[k] - eight * t2 * falloff * gd * d[k];
+            }
+        }
+
+        let scale: F = constant(SCALE[N - 2]);
+        for g in grad.iter_mut() {
+            *g *= scale;
+        }
+        (value * scale, grad)
+    }
+}
+
+impl_noise_all!(Simplex);
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec2, Vec3, Vec4};
+    use crate::noise::Noise;
+    use crate::noise::tests::{check_gradient, range};
+    use super::Simplex;
+
+    #[test]
+    fn test_deterministic() {
+        let a = Simplex::new(42);
+        let b = Simplex::new(42);
+        let c = Simplex::new(43);
+        let p = Vec4::new(1.3_f64, -7.25, 100.5, 0.125);
+        assert_eq!(a.sample(p), b.sample(p));
+        assert!(a.sample(p).0 != c.sample(p).0);
+    }
+
+    #[test]
+    fn test_gradient() {
+        check_gradient(&Simplex::new(3), 1e-5);
+    }
+
+    #[test]
+    fn test_range() {
+        let n = Simplex::new(11);
+        let (lo, hi) = range(&n, |t| Vec2::new(t * 0.0731, t * 0.0419));
+        assert!(lo >= -1.0 && hi <= 1.0);
+        assert!(lo < -0.3 && hi > 0.3);
+        let (lo, hi) = range(&n, |t| Vec3::new(t * 0.0731, t * 0.0419, t * 0.0173));
+        assert!(lo >= -1.0 && hi <= 1.0);
+        assert!(lo < -0.3 && hi > 0.3);
+        let (lo, hi) = range(&n, |t| Vec4::new(t * 0.0731, t * 0.0419, t * 0.0173, t * 0.0587));
+        assert!(lo >= -1.0 && hi <= 1.0);
+        assert!(lo < -0.3 && hi > 0.3);
+    }
+}
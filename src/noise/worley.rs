@@ -0,0 +1,161 @@
+
+use crate::{FullFloat, Vec2, Vec3, Vec4};
+use super::{splitmix64, hash_coords, lattice, constant};
+
+/// Seeded Worley (cellular) noise.
+///
+/// Each integer lattice cell holds one feature point at a hashed position.
+/// The value is the Euclidean distance to the nearest feature point (F1),
+/// which lies between 0 and sqrt(N).  The gradient is the unit vector
+/// pointing away from that feature point (zero exactly on it).
+#[derive(Debug, Clone)]
+pub struct Worley {
+    seed: u64,
+}
+
+impl Worley {
+    pub fn new(seed: u64) -> Worley {
+        Worley {
+            seed,
+        }
+    }
+
+    // Position of the feature point within the given cell
+    fn feature<F: FullFloat, const N: usize>(&self, c: [i64; N]) -> [F; N] {
+        let mut h = hash_coords(self.seed, c);
+        let mut out = [F::zero(); N];
+        for k in 0..N {
+            h = splitmix64(h);
+            // top 53 bits as a fraction in [0,1)
+            let frac = (h >> 11) as f64 / (1_u64 << 53) as f64;
+            out[k] = constant::<F>(c[k] as f64) + constant::<F>(frac);
+        }
+        out
+    }
+
+    fn eval<F: FullFloat, const N: usize>(&self, p: [F; N]) -> (F, [F; N]) {
+        let mut cell = [0_i64; N];
+        let mut f = [F::zero(); N];
+        for k in 0..N {
+            let (c, t) = lattice(p[k]);
+            cell[k] = c;
+            f[k] = t;
+        }
+
+        // The feature point in our own cell is at most sqrt(N) <= 2 away, so
+        // searching two cells out in each direction is always sufficient.
+        // Cells whose boundary is already farther than the best match are
+        // skipped.
+        let mut best = F::infinity();
+        let mut best_d = [F::zero(); N];
+        let mut count = 1;
+        for _ in 0..N {
+            count *= 5;
+        }
+        for i in 0..count {
+            let mut idx = i;
+            let mut c = cell;
+            let mut box_dist = F::zero();
+            for k in 0..N {
+                let o = (idx % 5) as i64 - 2;
+                idx /= 5;
+                c[k] = c[k].wrapping_add(o);
+                let gap = if o < 0 {
+                    f[k] + constant(-(o + 1) as f64)
+                } else if o > 0 {
+                    F::one() - f[k] + constant((o - 1) as f64)
+                } else {
+                    F::zero()
+                };
+                box_dist += gap * gap;
+            }
+            if box_dist >= best {
+                continue;
+            }
+
+            let feature: [F; N] = self.feature(c);
+            let mut d = [F::zero(); N];
+            let mut dist = F::zero();
+            for k in 0..N {
+                d[k] = p[k] - feature[k];
+                dist += d[k] * d[k];
+            }
+            if dist < best {
+                best = dist;
+                best_d = d;
+            }
+        }
+
+        let value = best.sqrt();
+        let mut grad = [F::zero(); N];
+        if value > F::zero() {
+            for k in 0..N {
+                grad[k] = best_d[k] / value;
+            }
+        }
+        (value, grad)
+    }
+}
+
+impl_noise_all!(Worley);
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec2, Vec3, Vec4};
+    use crate::noise::Noise;
+    use crate::noise::tests::{check_gradient, range};
+    use super::Worley;
+
+    #[test]
+    fn test_deterministic() {
+        let a = Worley::new(42);
+        let b = Worley::new(42);
+        let c = Worley::new(43);
+        let p = Vec2::new(1.3_f64, -7.25);
+        assert_eq!(a.sample(p), b.sample(p));
+        assert!(a.sample(p).0 != c.sample(p).0);
+    }
+
+    #[test]
+    fn test_gradient() {
+        check_gradient(&Worley::new(3), 1e-5);
+    }
+
+    #[test]
+    fn test_matches_brute_force() {
+        let n = Worley::new(9);
+        for i in 0..200 {
+            let t = i as f64 * 0.377;
+            let p = [t * 0.9 - 5.0, t * -1.3 + 2.0, t * 0.4];
+            let (v, _) = n.sample(Vec3::new(p[0], p[1], p[2]));
+            let mut best = f64::MAX;
+            for x in -4..5 {
+                for y in -4..5 {
+                    for z in -4..5 {
+                        let c = [p[0].floor() as i64 + x,
+                                 p[1].floor() as i64 + y,
+                                 p[2].floor() as i64 + z];
+                        let f: [f64; 3] = n.feature(c);
+                        let d = (0..3).map(|k| (p[k] - f[k]) * (p[k] - f[k]))
+                            .sum::<f64>();
+                        best = best.min(d);
+                    }
+                }
+            }
+            assert_eq!(v, best.sqrt());
+        }
+    }
+
+    #[test]
+    fn test_range() {
+        let n = Worley::new(11);
+        let (lo, hi) = range(&n, |t| Vec2::new(t * 0.0731, t * 0.0419));
+        assert!(lo >= 0.0 && hi <= 2.0_f64.sqrt());
+        let (lo, hi) = range(&n, |t| Vec3::new(t * 0.0731, t * 0.0419, t * 0.0173));
+        assert!(lo >= 0.0 && hi <= 3.0_f64.sqrt());
+        let (lo, hi) = range(&n, |t| Vec4::new(t * 0.0731, t * 0.0419, t * 0.0173, t * 0.0587));
+        assert!(lo >= 0.0 && hi <= 2.0);
+    }
+}
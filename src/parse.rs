@@ -0,0 +1,128 @@
+
+use std::error::Error;
+use std::fmt;
+use crate::{FullFloat, MathError};
+
+/// An error from parsing one of the math types from a string
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// A token could not be parsed as a number
+    InvalidNumber(String),
+    /// The wrong number of components was supplied
+    WrongCount { expected: usize, found: usize },
+    /// A direction or rotation was given with zero length
+    ZeroLength,
+    /// An angle had a unit suffix we do not recognize
+    UnknownUnit(String),
+    /// A direction or rotation had a NaN or infinite component
+    NonFinite,
+    /// The parsed values were rejected for some other reason
+    Invalid(MathError),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::InvalidNumber(ref s) => write!(f, "invalid number: {:?}", s),
+            ParseError::WrongCount { expected, found } =>
+                write!(f, "expected {} components, found {}", expected, found),
+            ParseError::ZeroLength => write!(f, "zero length"),
+            ParseError::UnknownUnit(ref s) => write!(f, "unknown unit: {:?}", s),
+            ParseError::NonFinite => write!(f, "non-finite value"),
+            ParseError::Invalid(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for ParseError { }
+
+// Parse exactly `n` numbers.  Brackets, parentheses, commas, semicolons and
+// whitespace all separate numbers, so "(1, 2, 3)", "[1,2,3]" and "1 2 3" are
+// all accepted.
+pub(crate) fn parse_floats<F: FullFloat>(s: &str, n: usize) -> Result<Vec<F>, ParseError> {
+    let is_separator = |c: char| c.is_whitespace()
+        || c == ',' || c == ';' || c == '(' || c == ')' || c == '[' || c == ']';
+
+    let mut out = Vec::with_capacity(n);
+    for token in s.split(is_separator).filter(|t| !t.is_empty()) {
+        match F::from_str_radix(token, 10) {
+            Ok(f) => out.push(f),
+            Err(_) => return Err(ParseError::InvalidNumber(token.to_owned())),
+        }
+    }
+    if out.len() != n {
+        return Err(ParseError::WrongCount { expected: n, found: out.len() });
+    }
+    Ok(out)
+}
+
+// The failures of normalizing a parsed direction or rotation
+pub(crate) fn normalize_error(e: MathError) -> ParseError {
+    match e {
+        MathError::ZeroLength => ParseError::ZeroLength,
+        MathError::NonFinite => ParseError::NonFinite,
+        e => ParseError::Invalid(e),
+    }
+}
+
+// Write "(a, b, c)", passing the formatter options (e.g. precision) on to
+// each component.
+pub(crate) fn write_list<F: fmt::Display>(f: &mut fmt::Formatter, items: &[F]) -> fmt::Result {
+    write!(f, "(")?;
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            write!(f, ", ")?;
+        }
+        fmt::Display::fmt(item, f)?;
+    }
+    write!(f, ")")
+}
+
+// Write a matrix given in row-major order as "[[a, b], [c, d]]", or with
+// one row per line under the alternate flag ("{:#}").
+pub(crate) fn write_matrix<F: fmt::Display>(f: &mut fmt::Formatter, rows: &[&[F]]) -> fmt::Result {
+    write!(f, "[")?;
+    for (r, row) in rows.iter().enumerate() {
+        if r > 0 {
+            if f.alternate() { write!(f, ",\n ")?; } else { write!(f, ", ")?; }
+        }
+        write!(f, "[")?;
+        for (c, item) in row.iter().enumerate() {
+            if c > 0 {
+                write!(f, ", ")?;
+            }
+            fmt::Display::fmt(item, f)?;
+        }
+        write!(f, "]")?;
+    }
+    write!(f, "]")
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_floats, normalize_error, ParseError};
+    use crate::MathError;
+
+    #[test]
+    fn test_parse_floats() {
+        assert_eq!(parse_floats::<f32>("(1, 2.5, -3)", 3), Ok(vec![1.0, 2.5, -3.0]));
+        assert_eq!(parse_floats::<f32>("10 20 30", 3), Ok(vec![10.0, 20.0, 30.0]));
+        assert_eq!(parse_floats::<f64>("[[1e3, 2],\n [3, 4]]", 4),
+                   Ok(vec![1000.0, 2.0, 3.0, 4.0]));
+        assert_eq!(parse_floats::<f32>("1 2", 3),
+                   Err(ParseError::WrongCount { expected: 3, found: 2 }));
+        assert_eq!(parse_floats::<f32>("1 x 2", 3),
+                   Err(ParseError::InvalidNumber("x".to_owned())));
+    }
+
+    #[test]
+    fn test_normalize_error() {
+        assert_eq!(normalize_error(MathError::ZeroLength), ParseError::ZeroLength);
+        assert_eq!(normalize_error(MathError::NonFinite), ParseError::NonFinite);
+        assert_eq!(normalize_error(MathError::Singular),
+                   ParseError::Invalid(MathError::Singular));
+        assert_eq!(normalize_error(MathError::NotNormalized).to_string(), "not normalized");
+    }
+}
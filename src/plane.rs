@@ -0,0 +1,55 @@
+
+use serde::{Serialize, Deserialize};
+use float_cmp::ApproxEq;
+use crate::{FullFloat, Vec3, Point3, Direction3};
+
+/// A plane, the points `p` where `normal · p == offset`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Plane<F> {
+    pub normal: Direction3<F>,
+    /// Signed distance of the plane from the origin, along the normal
+    pub offset: F,
+}
+
+impl<F: FullFloat> Plane<F> {
+    #[inline]
+    pub fn new(normal: Direction3<F>, offset: F) -> Plane<F> {
+        Plane { normal, offset }
+    }
+}
+
+impl<F: FullFloat> Plane<F> {
+    pub fn from_point_normal(point: &Point3<F>, normal: Direction3<F>) -> Plane<F> {
+        Plane::new(normal, (*normal).dot(point.0))
+    }
+}
+
+impl<F: FullFloat> Plane<F> {
+    /// The plane through three points, facing the side from which they
+    /// appear counter-clockwise.  Returns None if they are collinear.
+    pub fn from_points(a: &Point3<F>, b: &Point3<F>, c: &Point3<F>) -> Option<Plane<F>> {
+        let n: Vec3<F> = (*b - *a).cross(*c - *a);
+        if n.squared_magnitude() == F::zero() {
+            return None;
+        }
+        Some(Plane::from_point_normal(a, From::from(n)))
+    }
+}
+
+impl<F: FullFloat> Plane<F> {
+    /// Positive on the side the normal faces
+    pub fn signed_distance(&self, p: &Point3<F>) -> F {
+        (*self.normal).dot(p.0) - self.offset
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Plane<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.normal.approx_eq(&other.normal, margin)
+            && self.offset.approx_eq(other.offset, margin)
+    }
+}
@@ -1,7 +1,10 @@
 
+use std::fmt;
+use std::str::FromStr;
 use serde::{Serialize, Deserialize};
 use float_cmp::ApproxEq;
-use crate::{Point3, NQuat};
+use crate::{FullFloat, Vec3, Point3, Quat, NQuat};
+use crate::parse::{ParseError, parse_floats, normalize_error};
 
 /// A position is a combination of a point and an orientation
 ///
@@ -15,10 +18,38 @@ pub struct Position<F> {
     pub ori: NQuat<F>,
 }
 
+// ----------------------------------------------------------------------------
+// Display / FromStr
+
+/// Formats as the point followed by the orientation quaternion,
+/// e.g. "(1, 2, 3) (0, 0, 0, 1)"
+impl<F: FullFloat + fmt::Display> fmt::Display for Position<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.point, f)?;
+        write!(f, " ")?;
+        fmt::Display::fmt(&self.ori, f)
+    }
+}
+
+/// Parses seven numbers: the point, then the orientation quaternion
+/// (x, y, z, w), which is normalized.
+impl<F: FullFloat> FromStr for Position<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Position<F>, ParseError> {
+        let e = parse_floats::<F>(s, 7)?;
+        let q = Quat::new(Vec3::new(e[3], e[4], e[5]), e[6]);
+        Ok(Position {
+            point: Point3::new(e[0], e[1], e[2]),
+            ori: NQuat::try_from_quat(q).map_err(normalize_error)?,
+        })
+    }
+}
+
 // ----------------------------------------------------------------------------
 // ApproxEq
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Position<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Position<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -27,3 +58,26 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Posit
             && self.ori.approx_eq(&other.ori, margin)
     }
 }
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec3, Point3, NQuat, ParseError};
+    use super::Position;
+
+    #[test]
+    fn test_display_from_str() {
+        let p = Position {
+            point: Point3::new(1.0_f32, 2.0, 3.5),
+            ori: NQuat::identity(),
+        };
+        assert_eq!(format!("{}", p), "(1, 2, 3.5) (0, 0, 0, 1)");
+        assert_eq!(format!("{}", p).parse::<Position<f32>>(), Ok(p));
+        assert!("1 2 3".parse::<Position<f32>>().is_err());
+        assert!("1 2 3 0 0 0 0".parse::<Position<f32>>().is_err());
+        assert_eq!("1 2 3 0 0 0 NaN".parse::<Position<f32>>(), Err(ParseError::NonFinite));
+        assert_eq!("1 2 3 0 1e20 0 0".parse::<Position<f32>>().map(|p| p.ori),
+                   Ok(NQuat::new_isnormal(Vec3::new(0.0, 1.0, 0.0), 0.0)));
+    }
+}
@@ -0,0 +1,192 @@
+
+//! Projection of world points to the screen, and back.
+//!
+//! Normalized device coordinates (NDC) follow Vulkan: x and y run from -1 to
+//! 1 with y pointing down the screen, and depth runs from 0 to 1.  For
+//! OpenGL-style NDC with y pointing up, use a viewport with negative height
+//! placed at the bottom edge, as Vulkan itself allows.
+
+use serde::{Serialize, Deserialize};
+use num_traits::NumCast;
+use crate::{FullFloat, Vec4, Point2, Point3, Direction3, Mat4};
+
+impl<F: FullFloat> Mat4<F> {
+    /// Transform a point by a projective matrix, including the perspective
+    /// divide.  Returns None if the point maps to infinity (w of zero).
+    pub fn project_point(&self, p: &Point3<F>) -> Option<Point3<F>> {
+        Point3::from_vec4(self * Vec4::new(p.x, p.y, p.z, F::one()))
+    }
+}
+
+/// A rectangle of the framebuffer, in pixels, with a depth range
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Viewport<F> {
+    /// Left edge
+    pub x: F,
+    /// Top edge
+    pub y: F,
+    pub width: F,
+    pub height: F,
+    pub min_depth: F,
+    pub max_depth: F,
+}
+
+impl<F: FullFloat> Viewport<F> {
+    /// A viewport with the full 0 to 1 depth range
+    pub fn new(x: F, y: F, width: F, height: F) -> Viewport<F> {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+            min_depth: F::zero(),
+            max_depth: F::one(),
+        }
+    }
+}
+
+impl<F: FullFloat> Viewport<F> {
+    /// Map normalized device coordinates to pixel x, y and depth
+    pub fn ndc_to_pixel(&self, ndc: &Point3<F>) -> Point3<F> {
+        let half: F = NumCast::from(0.5_f32).unwrap();
+        Point3::new(self.x + (ndc.x + F::one()) * self.width * half,
+                    self.y + (ndc.y + F::one()) * self.height * half,
+                    self.min_depth + ndc.z * (self.max_depth - self.min_depth))
+    }
+}
+
+impl<F: FullFloat> Viewport<F> {
+    /// Map pixel x, y and depth to normalized device coordinates
+    pub fn pixel_to_ndc(&self, pixel: &Point3<F>) -> Point3<F> {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        Point3::new((pixel.x - self.x) * two / self.width - F::one(),
+                    (pixel.y - self.y) * two / self.height - F::one(),
+                    (pixel.z - self.min_depth) / (self.max_depth - self.min_depth))
+    }
+}
+
+impl<F: FullFloat> Viewport<F> {
+    /// The pixel position and depth of a world point, given the combined
+    /// view-projection matrix.  Returns None for points behind the camera.
+    pub fn project(&self, view_proj: &Mat4<F>, world: &Point3<F>) -> Option<Point3<F>> {
+        let clip = view_proj * Vec4::new(world.x, world.y, world.z, F::one());
+        if clip.w <= F::zero() {
+            return None;
+        }
+        let ndc = Point3::from_vec4(clip)?;
+        Some(self.ndc_to_pixel(&ndc))
+    }
+}
+
+impl<F: FullFloat> Viewport<F> {
+    /// The world point at a pixel position and depth.
+    ///
+    /// Takes the inverse of the view-projection matrix, so that it need
+    /// only be computed once per frame.
+    pub fn unproject(&self, inv_view_proj: &Mat4<F>, pixel: &Point3<F>) -> Option<Point3<F>> {
+        let ndc = self.pixel_to_ndc(pixel);
+        inv_view_proj.project_point(&ndc)
+    }
+}
+
+impl<F: FullFloat> Viewport<F> {
+    /// The world-space ray through a pixel: its origin on the near plane and
+    /// its direction away from the camera.
+    ///
+    /// Takes the inverse of the view-projection matrix, as `unproject` does.
+    pub fn pixel_ray(&self, inv_view_proj: &Mat4<F>, pixel: &Point2<F>)
+                     -> Option<(Point3<F>, Direction3<F>)>
+    {
+        let near = self.unproject(inv_view_proj, &Point3::new(pixel.x, pixel.y, self.min_depth))?;
+        let far = self.unproject(inv_view_proj, &Point3::new(pixel.x, pixel.y, self.max_depth))?;
+        let d = far - near;
+        if d.squared_magnitude() == F::zero() {
+            return None;
+        }
+        Some((near, From::from(d)))
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Vec3, Vec4, Point2, Point3, Direction3, Mat4};
+    use super::Viewport;
+
+    // Right handed, looking down -Z, Vulkan clip space (y down, depth 0..1)
+    fn perspective(fovy: f64, aspect: f64, near: f64, far: f64) -> Mat4<f64> {
+        let f = 1.0 / (fovy / 2.0).tan();
+        Mat4::new(f / aspect, 0.0, 0.0, 0.0,
+                  0.0, -f, 0.0, 0.0,
+                  0.0, 0.0, far / (near - far), near * far / (near - far),
+                  0.0, 0.0, -1.0, 0.0)
+    }
+
+    fn camera() -> (Mat4<f64>, Mat4<f64>) {
+        let proj = perspective(1.0, 16.0 / 9.0, 0.1, 1000.0);
+        // camera at (0, 2, 10) looking down -Z
+        let mut view = Mat4::identity();
+        view.set_translation(Point3::new(0.0, -2.0, -10.0));
+        let vp = proj * view;
+        let inv = vp.inverse().unwrap();
+        (vp, inv)
+    }
+
+    #[test]
+    fn test_from_vec4() {
+        assert_eq!(Point3::from_vec4(Vec4::new(2.0_f64, 4.0, 6.0, 2.0)),
+                   Some(Point3::new(1.0, 2.0, 3.0)));
+        assert_eq!(Point3::from_vec4(Vec4::new(2.0_f64, 4.0, 6.0, 0.0)), None);
+        assert_eq!(Direction3::from_vec4(Vec4::new(0.0_f64, 3.0, 0.0, 0.0)),
+                   Some(Direction3::new_isnormal(0.0, 1.0, 0.0)));
+        assert_eq!(Direction3::from_vec4(Vec4::new(0.0_f64, 3.0, 0.0, 1.0)), None);
+        assert_eq!(Direction3::from_vec4(Vec4::new(0.0_f64, 0.0, 0.0, 0.0)), None);
+    }
+
+    #[test]
+    fn test_project() {
+        let (vp, _) = camera();
+        let viewport = Viewport::new(0.0, 0.0, 1920.0, 1080.0);
+
+        // straight ahead lands in the middle of the screen
+        let p = viewport.project(&vp, &Point3::new(0.0, 2.0, -50.0)).unwrap();
+        assert!((p.x - 960.0).abs() < 1e-9);
+        assert!((p.y - 540.0).abs() < 1e-9);
+        assert!(p.z > 0.0 && p.z < 1.0);
+
+        // above the camera is higher up the screen (smaller y)
+        let up = viewport.project(&vp, &Point3::new(0.0, 5.0, -50.0)).unwrap();
+        assert!(up.y < 540.0);
+
+        // behind the camera is rejected
+        assert!(viewport.project(&vp, &Point3::new(0.0, 2.0, 20.0)).is_none());
+    }
+
+    #[test]
+    fn test_unproject() {
+        let (vp, inv) = camera();
+        let viewport = Viewport::new(100.0, 50.0, 800.0, 600.0);
+        let world = Point3::new(3.0, -1.0, -25.0);
+        let pixel = viewport.project(&vp, &world).unwrap();
+        let back = viewport.unproject(&inv, &pixel).unwrap();
+        assert!(back.approx_eq(&world, (1e-9, 1024)));
+    }
+
+    #[test]
+    fn test_pixel_ray() {
+        let (vp, inv) = camera();
+        let viewport = Viewport::new(0.0, 0.0, 1920.0, 1080.0);
+        let world = Point3::new(-4.0, 7.0, -60.0);
+        let pixel = viewport.project(&vp, &world).unwrap();
+        let (origin, dir) = viewport.pixel_ray(&inv, &Point2::new(pixel.x, pixel.y)).unwrap();
+
+        // the ray starts on the near plane and passes through the point
+        assert!((origin.z - (10.0 - 0.1)).abs() < 1e-6);
+        let to_world: Vec3<f64> = world - origin;
+        assert!(to_world.cross(*dir).magnitude() < 1e-6);
+        assert!(to_world.dot(*dir) > 0.0);
+    }
+}
@@ -1,10 +1,13 @@
 
-use std::ops::{Add, Sub, Mul, AddAssign, SubAssign, MulAssign};
+use std::ops::{Add, Sub, Mul, Div, AddAssign, SubAssign, MulAssign};
+use std::fmt;
+use std::str::FromStr;
 use serde::{Serialize, Deserialize};
 use num_traits::NumCast;
 use std::default::Default;
 use float_cmp::ApproxEq;
-use crate::{FullFloat, Vec3, Mat3, Angle, Direction3};
+use crate::{FullFloat, Vec3, Mat3, Angle, Direction3, MathError};
+use crate::parse::{ParseError, parse_floats, write_list, normalize_error};
 
 /// Quaternion (general)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -25,17 +28,23 @@ pub struct NQuat<F> {
 impl<F: FullFloat> Quat<F> {
     pub fn new(v: Vec3<F>, w: F) -> Quat<F> {
         Quat {
-            v: v,
-            w: w,
+            v,
+            w,
         }
     }
 }
 
 impl<F: FullFloat> NQuat<F> {
+    /// Create from components that are already normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the quaternion is not of unit length.  Use `try_new` to
+    /// check without panicking.
     pub fn new_isnormal(v: Vec3<F>, w: F) -> NQuat<F> {
         let q = NQuat {
-            v: v,
-            w: w,
+            v,
+            w,
         };
 
         let margin: F::Margin = Default::default();
@@ -46,6 +55,21 @@ impl<F: FullFloat> NQuat<F> {
     }
 }
 
+impl<F: FullFloat> NQuat<F> {
+    /// Create from components that are already normalized, or fail with
+    /// `NonFinite` or `NotNormalized`
+    pub fn try_new(v: Vec3<F>, w: F) -> Result<NQuat<F>, MathError> {
+        if !(v.x.is_finite() && v.y.is_finite() && v.z.is_finite() && w.is_finite()) {
+            return Err(MathError::NonFinite);
+        }
+        let margin: F::Margin = Default::default();
+        if !(w*w + v.squared_magnitude()).sqrt().approx_eq(F::one(), margin) {
+            return Err(MathError::NotNormalized);
+        }
+        Ok(NQuat { v, w })
+    }
+}
+
 impl<F: FullFloat> Quat<F> {
     pub fn identity() -> Quat<F> {
         Quat {
@@ -114,6 +138,13 @@ impl From<NQuat<f64>> for NQuat<f32> {
 // ----------------------------------------------------------------------------
 // Casting to/from normal form
 
+/// Normalizes the quaternion.
+///
+/// # Panics
+///
+/// Panics if the quaternion has zero length or is not finite.  Use
+/// `NQuat::try_from_quat` when that is possible.  (`TryFrom<Quat>` would
+/// conflict with this `From` through the blanket `TryFrom` for any `Into`.)
 impl<F: FullFloat> From<Quat<F>> for NQuat<F>
 {
     fn from(q: Quat<F>) -> NQuat<F> {
@@ -124,6 +155,29 @@ impl<F: FullFloat> From<Quat<F>> for NQuat<F>
     }
 }
 
+impl<F: FullFloat> NQuat<F> {
+    /// Normalize a quaternion, or fail with `NonFinite` or `ZeroLength`
+    pub fn try_from_quat(q: Quat<F>) -> Result<NQuat<F>, MathError> {
+        if !(q.v.x.is_finite() && q.v.y.is_finite() && q.v.z.is_finite() && q.w.is_finite()) {
+            return Err(MathError::NonFinite);
+        }
+        let mag2 = q.squared_magnitude();
+        if mag2.is_normal() {
+            let mag = mag2.sqrt();
+            return Ok(NQuat { v: q.v / mag, w: q.w / mag });
+        }
+        // Otherwise scale by the largest component first, so the magnitude
+        // can neither overflow nor underflow
+        let big = q.v.x.abs().max(q.v.y.abs()).max(q.v.z.abs()).max(q.w.abs());
+        if big == F::zero() {
+            return Err(MathError::ZeroLength);
+        }
+        let (v, w) = (q.v / big, q.w / big);
+        let mag = (v.squared_magnitude() + w * w).sqrt();
+        Ok(NQuat { v: v / mag, w: w / mag })
+    }
+}
+
 impl<F: FullFloat> From<NQuat<F>> for Quat<F> {
     fn from(nq: NQuat<F>) -> Quat<F> {
         Quat { v: nq.v, w: nq.w }
@@ -149,13 +203,18 @@ impl<F: FullFloat> NQuat<F> {
 }
 
 impl<F: FullFloat> NQuat<F> {
-    /// Determine the axis/angle representation of an NQuat
+    /// Determine the axis/angle representation of an NQuat.  The identity
+    /// has no axis, and is given as zero about the X axis.
     pub fn as_axis_angle(&self) -> (Direction3<F>, Angle<F>)
     {
         let two: F = NumCast::from(2.0_f32).unwrap();
-        let angle = self.w.acos() * two;
-        let axis = self.v;
-        (From::from(axis), Angle::from_radians(angle))
+        let w = self.w.max(-F::one()).min(F::one());
+        let angle = w.acos() * two;
+        let axis = match Direction3::try_from_vec(self.v) {
+            Ok(axis) => axis,
+            Err(_) => Direction3::new_isnormal(F::one(), F::zero(), F::zero()),
+        };
+        (axis, Angle::from_radians(angle))
     }
 }
 
@@ -163,7 +222,8 @@ impl<F: FullFloat> NQuat<F> {
 // Compute the quat that rotates from start to end
 
 impl<F: FullFloat> NQuat<F> {
-    // This returns None if start/end are the same or opposite)
+    // This returns None if start/end are opposite.  See rotation_between()
+    // for a version that handles that case.
     pub fn from_directions(start: Direction3<F>, end: Direction3<F>) -> Option<NQuat<F>>
     {
         let margin: F::Margin = Default::default();
@@ -197,6 +257,102 @@ impl<F: FullFloat> NQuat<F> {
     }
 }
 
+impl<F: FullFloat> NQuat<F> {
+    /// The shortest rotation taking `start` to `end`.  Unlike
+    /// `from_directions` this never fails: when the directions are opposite
+    /// it is a half turn about an axis perpendicular to `start`, chosen the
+    /// same way every time.
+    pub fn rotation_between(start: Direction3<F>, end: Direction3<F>) -> NQuat<F>
+    {
+        // Below this the directions are opposite to within rounding, and
+        // the half-way direction below is no more accurate than any other
+        // perpendicular axis
+        let tolerance = F::epsilon().sqrt();
+        let sum = *start + *end;
+        let mag = sum.magnitude();
+        if mag <= tolerance {
+            return NQuat::from_axis_angle(&perpendicular(start),
+                                          &Angle::from_radians(F::PI()));
+        }
+        // The half-way direction: rotating by twice the angle from start to
+        // half gives end.  This stays accurate much closer to opposite than
+        // the (1 + dot, cross) form does.
+        let half = sum / mag;
+        let q = Quat::new((*start).cross(half), (*start).dot(half));
+        From::from(q)
+    }
+}
+
+// Any unit vector perpendicular to d, crossing it with whichever axis it is
+// least aligned with
+pub(crate) fn perpendicular<F: FullFloat>(d: Direction3<F>) -> Direction3<F> {
+    let (x, y, z) = (d.x.abs(), d.y.abs(), d.z.abs());
+    let axis = if x <= y && x <= z {
+        Vec3::new(F::one(), F::zero(), F::zero())
+    } else if y <= z {
+        Vec3::new(F::zero(), F::one(), F::zero())
+    } else {
+        Vec3::new(F::zero(), F::zero(), F::one())
+    };
+    From::from((*d).cross(axis))
+}
+
+impl<F: FullFloat> NQuat<F> {
+    /// The rotation taking `start` to `end` that does not roll relative to
+    /// `up`: whatever was upright (with respect to `up`) when facing `start`
+    /// is still upright when facing `end`.  This is the usual "turn to
+    /// face" rotation.  If either direction is parallel to `up` there is no
+    /// upright to keep, and this is the same as `rotation_between`.
+    pub fn from_directions_with_up(start: Direction3<F>, end: Direction3<F>,
+                                   up: Direction3<F>) -> NQuat<F>
+    {
+        From::from(Mat3::from_directions_with_up(start, end, up))
+    }
+}
+
+impl<F: FullFloat> Mat3<F> {
+    /// The shortest rotation taking `start` to `end`.  See
+    /// `NQuat::rotation_between`.
+    pub fn rotation_between(start: Direction3<F>, end: Direction3<F>) -> Mat3<F>
+    {
+        From::from(NQuat::rotation_between(start, end))
+    }
+}
+
+impl<F: FullFloat> Mat3<F> {
+    /// The rotation taking `start` to `end` without rolling relative to
+    /// `up`.  See `NQuat::from_directions_with_up`.
+    pub fn from_directions_with_up(start: Direction3<F>, end: Direction3<F>,
+                                   up: Direction3<F>) -> Mat3<F>
+    {
+        // Build an orthonormal frame (forward, up, side) at each end; the
+        // rotation maps one to the other.
+        // Below the tolerance (as in `rotation_between`) `f` is parallel
+        // to `up` to within rounding.  Just above it the projection has lost
+        // up to half its digits to cancellation, so it is projected a second
+        // time to make it perpendicular to working precision.
+        let tolerance = F::epsilon().sqrt();
+        let frame = |f: Direction3<F>| -> Option<Mat3<F>> {
+            let u = *up - *f * (*f).dot(*up);
+            let mag = u.magnitude();
+            if mag <= tolerance {
+                return None;
+            }
+            let u = u / mag;
+            let u = u - *f * (*f).dot(u);
+            let u = u / u.magnitude();
+            Some(Mat3::from_cols(*f, u, (*f).cross(u)))
+        };
+        match (frame(start), frame(end)) {
+            (Some(mut a), Some(b)) => {
+                a.transpose();
+                b * a
+            },
+            _ => Mat3::rotation_between(start, end),
+        }
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Magnitude
 
@@ -290,8 +446,7 @@ impl<F: FullFloat> MulAssign<F> for Quat<F>
 impl<F: FullFloat> MulAssign for Quat<F>
 {
     fn mul_assign(&mut self, rhs: Quat<F>) {
-        self.v = self.v.cross(rhs.v)  +  rhs.v * self.w  +  self.v * rhs.w;
-        self.w = self.w * rhs.w  -  self.v.dot(rhs.v);
+        *self = *self * rhs;
     }
 }
 
@@ -356,6 +511,232 @@ impl<F: FullFloat> NQuat<F>
     }
 }
 
+// ----------------------------------------------------------------------------
+// Inverse and division
+
+impl<F: FullFloat> Quat<F>
+{
+    /// The multiplicative inverse, or None for the zero quaternion
+    pub fn inverse(&self) -> Option<Quat<F>> {
+        let sm = self.squared_magnitude();
+        if sm == F::zero() {
+            return None;
+        }
+        Some(self.conjugate() * (F::one() / sm))
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// The inverse rotation (the same as the conjugate)
+    #[inline]
+    pub fn inverse(&self) -> NQuat<F> {
+        self.conjugate()
+    }
+}
+
+/// `a / b` is `a * b.inverse()`. Dividing by zero yields non-finite values.
+impl<F: FullFloat> Div for Quat<F>
+{
+    type Output = Quat<F>;
+
+    fn div(self, rhs: Quat<F>) -> Quat<F> {
+        let sm = rhs.squared_magnitude();
+        self * (rhs.conjugate() * (F::one() / sm))
+    }
+}
+
+impl<F: FullFloat> Div for NQuat<F>
+{
+    type Output = NQuat<F>;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: NQuat<F>) -> NQuat<F> {
+        self * rhs.conjugate()
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Exponential, logarithm and power
+
+impl<F: FullFloat> Quat<F>
+{
+    pub fn exp(&self) -> Quat<F> {
+        let ew = self.w.exp();
+        let theta = self.v.magnitude();
+        if theta == F::zero() {
+            return Quat::new(self.v, ew);
+        }
+        let (s, c) = theta.sin_cos();
+        Quat::new(self.v * (ew * s / theta), ew * c)
+    }
+}
+
+impl<F: FullFloat> Quat<F>
+{
+    /// The principal logarithm.  A negative real quaternion has no unique
+    /// logarithm; its vector part is taken along X.
+    pub fn ln(&self) -> Quat<F> {
+        let mag = self.magnitude();
+        let vmag = self.v.magnitude();
+        let theta = vmag.atan2(self.w);
+        let v = if vmag > F::zero() {
+            self.v * (theta / vmag)
+        } else if self.w < F::zero() {
+            Vec3::new(theta, F::zero(), F::zero())
+        } else {
+            self.v
+        };
+        Quat::new(v, mag.ln())
+    }
+}
+
+impl<F: FullFloat> Quat<F>
+{
+    /// Raise to a real power, `exp(t ln(q))`
+    pub fn powf(&self, t: F) -> Quat<F> {
+        (self.ln() * t).exp()
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// The rotation whose logarithm is the pure quaternion `v`: a rotation
+    /// about `v` by twice its length
+    pub fn exp(v: Vec3<F>) -> NQuat<F> {
+        From::from(Quat::new(v, F::zero()).exp())
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// The logarithm, which is a pure quaternion: the rotation axis scaled
+    /// by half the rotation angle
+    pub fn ln(&self) -> Vec3<F> {
+        let q: Quat<F> = From::from(*self);
+        q.ln().v
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// A fraction `t` of this rotation (about the same axis, through `t`
+    /// times the angle)
+    pub fn powf(&self, t: F) -> NQuat<F> {
+        NQuat::exp(self.ln() * t)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Swing-twist decomposition and joint limits
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// The same rotation with a non-negative w, so that its angle is at
+    /// most a half turn
+    fn shortest(&self) -> NQuat<F> {
+        if self.w < F::zero() {
+            NQuat { v: -self.v, w: -self.w }
+        } else {
+            *self
+        }
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// Split into `(swing, twist)` such that `self == swing * twist`.
+    /// `twist` rotates about `axis` and `swing` about an axis perpendicular
+    /// to it.  When `self` turns `axis` exactly around, the twist is not
+    /// defined and is taken as the identity.
+    pub fn swing_twist(&self, axis: Direction3<F>) -> (NQuat<F>, NQuat<F>) {
+        let p = *axis * self.v.dot(*axis);
+        let twist = NQuat::try_from_quat(Quat::new(p, self.w))
+            .unwrap_or_else(|_| NQuat::identity());
+        (*self * twist.conjugate(), twist)
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// The signed angle of the twist about `axis`, from -π to π
+    pub fn twist_angle(&self, axis: Direction3<F>) -> Angle<F> {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        let q = self.shortest();
+        Angle::from_radians(two * q.v.dot(*axis).atan2(q.w))
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// Limit the rotation angle to `max`, keeping the axis.  Applied to a
+    /// swing, this keeps the twist axis within a cone of half-angle `max`.
+    pub fn clamp_cone(&self, max: Angle<F>) -> NQuat<F> {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        let q = self.shortest();
+        let r = q.ln();
+        let half = r.magnitude();
+        let max_half = max.as_radians() / two;
+        if half <= max_half {
+            return q;
+        }
+        NQuat::exp(r * (max_half / half))
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// Limit a swing to an elliptical cone, allowing up to `max_a` of
+    /// rotation about `axis_a` and up to `max_b` about `axis_b`, the two
+    /// being perpendicular to each other and to the twist axis.  A swing
+    /// outside the ellipse is scaled back onto it, keeping its axis.
+    pub fn clamp_elliptical_cone(&self, axis_a: Direction3<F>, axis_b: Direction3<F>,
+                                 max_a: Angle<F>, max_b: Angle<F>) -> NQuat<F>
+    {
+        let q = self.shortest();
+        let r = q.ln();
+        let (half_a, half_b) = (r.dot(*axis_a), r.dot(*axis_b));
+        let (max_a, max_b) = (max_a.as_radians(), max_b.as_radians());
+        let two: F = NumCast::from(2.0_f32).unwrap();
+
+        // A zero limit removes that component outright
+        let half_a = if max_a == F::zero() { F::zero() } else { half_a };
+        let half_b = if max_b == F::zero() { F::zero() } else { half_b };
+        let term = |half: F, max: F| if half == F::zero() { F::zero() } else {
+            let e = two * half / max;
+            e * e
+        };
+        let e = term(half_a, max_a) + term(half_b, max_b);
+        let scale = if e > F::one() { F::one() / e.sqrt() } else { F::one() };
+        NQuat::exp((*axis_a * half_a + *axis_b * half_b) * scale)
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// Limit the twist about `axis` to between `min` and `max`, discarding
+    /// any swing
+    pub fn clamp_twist(&self, axis: Direction3<F>, min: Angle<F>, max: Angle<F>) -> NQuat<F> {
+        let angle = self.twist_angle(axis).as_radians()
+            .max(min.as_radians())
+            .min(max.as_radians());
+        NQuat::from_axis_angle(&axis, &Angle::from_radians(angle))
+    }
+}
+
+impl<F: FullFloat> NQuat<F>
+{
+    /// Apply a typical joint limit: split into swing and twist about
+    /// `axis`, limit the swing to a cone of half-angle `max_swing` and the
+    /// twist to between `min_twist` and `max_twist`, and recombine
+    pub fn clamp_swing_twist(&self, axis: Direction3<F>, max_swing: Angle<F>,
+                             min_twist: Angle<F>, max_twist: Angle<F>) -> NQuat<F>
+    {
+        let (swing, twist) = self.swing_twist(axis);
+        swing.clamp_cone(max_swing) * twist.clamp_twist(axis, min_twist, max_twist)
+    }
+}
+
 // ----------------------------------------------------------------------------
 // Rotate a vector
 
@@ -385,6 +766,16 @@ impl<F: FullFloat> Mul<Vec3<F>> for NQuat<F>
     }
 }
 
+// Rotation preserves length, so directions stay directions
+impl<F: FullFloat> Mul<Direction3<F>> for NQuat<F>
+{
+    type Output = Direction3<F>;
+
+    fn mul(self, rhs: Direction3<F>) -> Direction3<F> {
+        From::from(self.rotate(*rhs))
+    }
+}
+
 // ----------------------------------------------------------------------------
 // To/From Matrix
 
@@ -411,6 +802,7 @@ impl<F: FullFloat> From<NQuat<F>> for Mat3<F> {
 
 impl<F: FullFloat> From<Mat3<F>> for NQuat<F> {
     fn from(m: Mat3<F>) -> NQuat<F> {
+        // m.c.r is the element at row r, column c
         let one: F = F::one();
         let half: F = NumCast::from(0.5_f32).unwrap();
         let quarter: F = NumCast::from(0.25_f32).unwrap();
@@ -423,35 +815,35 @@ impl<F: FullFloat> From<Mat3<F>> for NQuat<F> {
         if sum>F::zero() {
             w = (sum + one).sqrt() * half;
             let f = quarter / w;
-            x = (m.z.y - m.y.z) * f;
-            y = (m.x.z - m.z.x) * f;
-            z = (m.y.x - m.x.y) * f;
+            x = (m.y.z - m.z.y) * f;
+            y = (m.z.x - m.x.z) * f;
+            z = (m.x.y - m.y.x) * f;
         }
         else if (m.x.x > m.y.y) && (m.x.x > m.z.z) {
             x = (m.x.x - m.y.y - m.z.z + one).sqrt() * half;
             let f = quarter / x;
-            y = (m.y.x + m.x.y) * f;
-            z = (m.x.z + m.z.x) * f;
-            w = (m.z.y - m.y.z) * f;
+            y = (m.x.y + m.y.x) * f;
+            z = (m.z.x + m.x.z) * f;
+            w = (m.y.z - m.z.y) * f;
         }
         else if m.y.y > m.z.z {
             y = (m.y.y - m.x.x - m.z.z + one).sqrt() * half;
             let f = quarter / y;
-            x = (m.y.x + m.x.y) * f;
-            z = (m.z.y + m.y.z) * f;
-            w = (m.x.z - m.z.x) * f;
+            x = (m.x.y + m.y.x) * f;
+            z = (m.y.z + m.z.y) * f;
+            w = (m.z.x - m.x.z) * f;
         }
         else {
             z = (m.z.z - m.x.x - m.y.y + one).sqrt() * half;
             let f = quarter / z;
-            x = (m.x.z + m.z.x) * f;
-            y = (m.z.y + m.y.z) * f;
-            w = (m.y.x - m.x.y) * f;
+            x = (m.z.x + m.x.z) * f;
+            y = (m.y.z + m.z.y) * f;
+            w = (m.x.y - m.y.x) * f;
         }
 
         let q = Quat {
-            v: Vec3 { x: x, y: y, z: z },
-            w: w
+            v: Vec3 { x, y, z },
+            w
         };
 
         // The above is theoretically normal, but instability will probably
@@ -460,10 +852,47 @@ impl<F: FullFloat> From<Mat3<F>> for NQuat<F> {
     }
 }
 
+// ----------------------------------------------------------------------------
+// Display / FromStr
+//
+// Quaternions are written as "(x, y, z, w)": the vector part, then the scalar.
+
+impl<F: FullFloat + fmt::Display> fmt::Display for Quat<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_list(f, &[self.v.x, self.v.y, self.v.z, self.w])
+    }
+}
+
+impl<F: FullFloat + fmt::Display> fmt::Display for NQuat<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_list(f, &[self.v.x, self.v.y, self.v.z, self.w])
+    }
+}
+
+impl<F: FullFloat> FromStr for Quat<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Quat<F>, ParseError> {
+        let e = parse_floats::<F>(s, 4)?;
+        Ok(Quat::new(Vec3::new(e[0], e[1], e[2]), e[3]))
+    }
+}
+
+/// Parses like a Quat, then normalizes. Fails on a zero or non-finite
+/// quaternion.
+impl<F: FullFloat> FromStr for NQuat<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<NQuat<F>, ParseError> {
+        let q: Quat<F> = s.parse()?;
+        NQuat::try_from_quat(q).map_err(normalize_error)
+    }
+}
+
 // ----------------------------------------------------------------------------
 // ApproxEq
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Quat<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Quat<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -473,7 +902,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Quat<
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a NQuat<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &NQuat<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -534,8 +963,8 @@ mod tests {
         let q2: Quat<f32> = From::from(nq);
         let q2c: Quat<f32> = q2.conjugate();
 
-        assert!(q2.approx_eq(&q, (2.0 * ::std::f32::EPSILON, 2)) ||
-                q2c.approx_eq(&q, (2.0 * ::std::f32::EPSILON, 2)));
+        assert!(q2.approx_eq(&q, (2.0 * f32::EPSILON, 2)) ||
+                q2c.approx_eq(&q, (2.0 * f32::EPSILON, 2)));
     }
 
     #[test]
@@ -548,8 +977,8 @@ mod tests {
         let (axis2, angle2) = q.as_axis_angle();
         println!("axis {:?} angle {:?} axis {:?} angle {:?}",
                  axis, angle, axis2, angle2);
-        assert!(axis.approx_eq(&axis2, (2.0 * ::std::f32::EPSILON, 2)));
-        assert!(angle.approx_eq(&angle2, (2.0 * ::std::f32::EPSILON, 2)));
+        assert!(axis.approx_eq(&axis2, (2.0 * f32::EPSILON, 2)));
+        assert!(angle.approx_eq(&angle2, (2.0 * f32::EPSILON, 2)));
     }
 
     #[test]
@@ -564,9 +993,312 @@ mod tests {
 
         let object2 = q.rotate(object);
 
-        assert!(object2.x.approx_eq(10.0, (2.0 * ::std::f32::EPSILON, 2)));
-        assert!(object2.y.approx_eq(-3.0, (2.0 * ::std::f32::EPSILON, 2)));
-        assert!(object2.z.approx_eq(5.0, (2.0 * ::std::f32::EPSILON, 2)));
+        assert!(object2.x.approx_eq(10.0, (2.0 * f32::EPSILON, 2)));
+        assert!(object2.y.approx_eq(-3.0, (2.0 * f32::EPSILON, 2)));
+        assert!(object2.z.approx_eq(5.0, (2.0 * f32::EPSILON, 2)));
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        let q = Quat::<f32>::new(Vec3::new(1.0, 2.0, 3.0), 4.5);
+        assert_eq!(format!("{}", q), "(1, 2, 3, 4.5)");
+        assert_eq!(format!("{:.1}", q), "(1.0, 2.0, 3.0, 4.5)");
+        assert_eq!(format!("{}", q).parse::<Quat<f32>>(), Ok(q));
+
+        let nq = NQuat::<f64>::identity();
+        assert_eq!(format!("{}", nq), "(0, 0, 0, 1)");
+        assert_eq!("(0, 0, 0, 2)".parse::<NQuat<f64>>(), Ok(nq));
+        assert_eq!("0 0 0 0".parse::<NQuat<f64>>(), Err(crate::ParseError::ZeroLength));
+        assert_eq!("1e20 0 0 0".parse::<NQuat<f32>>(),
+                   Ok(NQuat::new_isnormal(Vec3::new(1.0, 0.0, 0.0), 0.0)));
+        assert_eq!("NaN 0 0 1".parse::<NQuat<f32>>(), Err(crate::ParseError::NonFinite));
+        assert_eq!("0 0 inf 1".parse::<NQuat<f32>>(), Err(crate::ParseError::NonFinite));
+    }
+
+    #[test]
+    fn test_mat_to_quat_rotates_same() {
+        use float_cmp::ApproxEq;
+
+        // cover each branch: small angle, and large angles about each axis
+        let v = Vec3::<f64>::new(0.3, -1.2, 0.7);
+        for &(x, y, z, degrees) in [(1.0, 1.0, 1.0, 30.0), (1.0, 0.1, 0.0, 170.0),
+                                    (0.1, 1.0, 0.0, 170.0), (0.0, 0.1, 1.0, 170.0)].iter() {
+            let axis: Direction3<f64> = From::from(Vec3::new(x, y, z));
+            let m = Mat3::rotate_axis_angle(axis, Angle::from_degrees(degrees));
+            let q: NQuat<f64> = From::from(m);
+            assert!(q.rotate(v).approx_eq(&(m * v), (1e-12, 8)));
+        }
+    }
+
+    #[test]
+    fn test_inverse_div() {
+        use float_cmp::ApproxEq;
+
+        let a = Quat::<f64>::new(Vec3::new(1.0, -2.0, 0.5), 3.0);
+        let b = Quat::<f64>::new(Vec3::new(0.25, 4.0, -1.0), -2.0);
+        let ai = a.inverse().unwrap();
+        assert!((a * ai).approx_eq(&Quat::identity(), (1e-15, 4)));
+        assert!((ai * a).approx_eq(&Quat::identity(), (1e-15, 4)));
+        assert!(((a * b) / b).approx_eq(&a, (1e-14, 4)));
+        assert_eq!(Quat::<f64>::new(Vec3::new(0.0, 0.0, 0.0), 0.0).inverse(), None);
+
+        let mut c = a;
+        c *= b;
+        assert_eq!(c, a * b);
+
+        let axis: Direction3<f64> = From::from(Vec3::new(1.0, 1.0, 0.0));
+        let p = NQuat::from_axis_angle(&axis, &Angle::from_degrees(50.0));
+        let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(20.0));
+        let r = NQuat::from_axis_angle(&axis, &Angle::from_degrees(30.0));
+        assert!((p / q).approx_eq(&r, (1e-15, 4)));
+        assert!((p * p.inverse()).approx_eq(&NQuat::identity(), (1e-15, 4)));
+    }
+
+    #[test]
+    fn test_exp_ln() {
+        use float_cmp::ApproxEq;
+
+        let margin = (1e-12, 8);
+        let general = Quat::<f64>::new(Vec3::new(0.3, -0.2, 0.9), -1.5);
+        assert!(general.ln().exp().approx_eq(&general, margin));
+
+        let axis: Direction3<f64> = From::from(Vec3::new(0.2, -1.0, 0.4));
+        for &degrees in [0.0, 1e-6, 0.01, 90.0, 179.999, 180.0].iter() {
+            let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(degrees));
+            assert!(NQuat::exp(q.ln()).approx_eq(&q, margin));
+            let qq: Quat<f64> = From::from(q);
+            assert!(qq.ln().exp().approx_eq(&qq, margin));
+        }
+
+        // the log of a rotation is half its angle along its axis
+        let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(180.0));
+        let l = q.ln();
+        assert!((l.magnitude() - ::std::f64::consts::FRAC_PI_2).abs() < 1e-12);
+        assert!(l.cross(*axis).magnitude() < 1e-12);
+
+        // -1 has no unique log, but exp must still recover it
+        let minus_one = Quat::<f64>::new(Vec3::new(0.0, 0.0, 0.0), -1.0);
+        assert!(minus_one.ln().exp().approx_eq(&minus_one, margin));
+    }
+
+    #[test]
+    fn test_powf() {
+        use float_cmp::ApproxEq;
+
+        let margin = (1e-12, 8);
+        let axis: Direction3<f64> = From::from(Vec3::new(0.0, 0.0, 1.0));
+        let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(120.0));
+        let third = NQuat::from_axis_angle(&axis, &Angle::from_degrees(40.0));
+        assert!(q.powf(1.0 / 3.0).approx_eq(&third, margin));
+        assert!(q.powf(0.0).approx_eq(&NQuat::identity(), margin));
+        assert!(q.powf(1.0).approx_eq(&q, margin));
+        assert!((q.powf(0.5) * q.powf(0.5)).approx_eq(&q, margin));
+
+        let g = Quat::<f64>::new(Vec3::new(1.0, 2.0, 3.0), 4.0);
+        assert!(g.powf(2.0).approx_eq(&(g * g), (1e-12, 8)));
+        assert!(g.powf(-1.0).approx_eq(&g.inverse().unwrap(), (1e-12, 8)));
+    }
+
+    #[test]
+    fn test_rotation_between_sweep() {
+        let axes = [Vec3::new(0.0_f64, 1.0, 0.0), Vec3::new(1.0, -2.0, 0.5),
+                    Vec3::new(0.0, 0.0, 1.0)];
+        let start: Direction3<f64> = From::from(Vec3::new(1.0, 0.25, -0.5));
+        for axis in axes.iter() {
+            // the part of the axis perpendicular to start, so the angle
+            // between start and end is exactly the rotation angle
+            let axis: Direction3<f64> = From::from(*axis - *start * (*start).dot(*axis));
+            // Within a whisker of opposite, the rotation is ill-conditioned
+            // and only as accurate as the square root of epsilon
+            let pi = std::f64::consts::PI;
+            let angles = (0..=180).map(|d| ((d as f64).to_radians(), 1e-12))
+                .chain(vec![(pi - 1e-4, 1e-10), (pi - 1e-7, 1e-7), (pi - 1e-10, 1e-7)]);
+            for (theta, tolerance) in angles {
+                let turn = NQuat::from_axis_angle(&axis, &Angle::from_radians(theta));
+                let end = turn * start;
+                let q = NQuat::rotation_between(start, end);
+                assert!((*(q * start) - *end).magnitude() < tolerance, "{}", theta);
+                let (_, angle) = q.as_axis_angle();
+                assert!((angle.as_radians() - theta).abs() < 1e-7, "{}", theta);
+
+                let m = Mat3::rotation_between(start, end);
+                assert!((m * *start - *end).magnitude() < tolerance);
+            }
+        }
+
+        // exactly opposite, along and off the axes
+        for v in [Vec3::new(1.0_f64, 0.0, 0.0), Vec3::new(0.0, 0.0, -1.0),
+                  Vec3::new(3.0, -1.0, 2.0)].iter() {
+            let start: Direction3<f64> = From::from(*v);
+            let q = NQuat::rotation_between(start, -start);
+            assert!((*(q * start) + *start).magnitude() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_from_directions_with_up() {
+        use float_cmp::ApproxEq;
+
+        let forward = Direction3::new_isnormal(0.0_f64, 0.0, -1.0);
+        let up = Direction3::new_isnormal(0.0_f64, 1.0, 0.0);
+        let targets = [Vec3::new(1.0_f64, 0.0, 0.0), Vec3::new(0.0, 0.0, 1.0),
+                       Vec3::new(1.0, 2.0, 1.0), Vec3::new(-0.3, -0.9, 0.1)];
+        for t in targets.iter() {
+            let end: Direction3<f64> = From::from(*t);
+            let q = NQuat::from_directions_with_up(forward, end, up);
+            assert!((*(q * forward) - *end).magnitude() < 1e-12);
+            // no roll: the rotated up stays in the vertical plane through
+            // end, on the upper side
+            let new_up = q * up;
+            assert!((*new_up).dot((*end).cross(*up)).abs() < 1e-12);
+            assert!((*new_up).dot(*up) > 0.0);
+
+            let m = Mat3::from_directions_with_up(forward, end, up);
+            let mq: Mat3<f64> = From::from(q);
+            assert!(m.approx_eq(&mq, (1e-12, 8)));
+        }
+
+        // facing straight up there is no upright to keep
+        let q = NQuat::from_directions_with_up(forward, up, up);
+        assert!(q.approx_eq(&NQuat::rotation_between(forward, up), (1e-12, 8)));
+
+        // nearly straight up in f32, on either side of the cutoff, the
+        // result must still be a rotation
+        let up = Direction3::new_isnormal(0.0_f32, 1.0, 0.0);
+        let forward = Direction3::new_isnormal(0.0_f32, 0.0, -1.0);
+        for &tilt in &[1e-3_f32, 5e-4, 3.5e-4, 1e-5] {
+            let end: Direction3<f32> = From::from(Vec3::new(tilt, 1.0, -tilt));
+            let m = Mat3::from_directions_with_up(forward, end, up);
+            assert!(m.is_rotation(1e-5));
+            assert!((m * *forward - *end).magnitude() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn test_swing_twist() {
+        use float_cmp::ApproxEq;
+
+        let margin = (1e-12, 8);
+        let axis = Direction3::new_isnormal(0.0_f64, 1.0, 0.0);
+        let twist_in = NQuat::from_axis_angle(&axis, &Angle::from_degrees(70.0));
+        let swing_axis: Direction3<f64> = From::from(Vec3::new(1.0, 0.0, -2.0));
+        let swing_in = NQuat::from_axis_angle(&swing_axis, &Angle::from_degrees(40.0));
+        let q = swing_in * twist_in;
+
+        let (swing, twist) = q.swing_twist(axis);
+        assert!((swing * twist).approx_eq(&q, margin));
+        assert!(swing.approx_eq(&swing_in, margin));
+        assert!(twist.approx_eq(&twist_in, margin));
+        assert!((q.twist_angle(axis).as_degrees() - 70.0).abs() < 1e-9);
+
+        // an arbitrary rotation still recombines, with a perpendicular swing
+        let odd: Direction3<f64> = From::from(Vec3::new(0.3, 0.5, 0.8));
+        let q = NQuat::from_axis_angle(&odd, &Angle::from_degrees(-130.0));
+        let (swing, twist) = q.swing_twist(axis);
+        assert!((swing * twist).approx_eq(&q, margin));
+        let s: Quat<f64> = From::from(swing);
+        assert!(s.v.dot(*axis).abs() < 1e-12);
+        let t: Quat<f64> = From::from(twist);
+        assert!(t.v.cross(*axis).magnitude() < 1e-12);
+
+        // a half turn about a perpendicular axis has no twist
+        let flip = NQuat::from_axis_angle(&swing_axis, &Angle::from_degrees(180.0));
+        let (swing, twist) = flip.swing_twist(axis);
+        assert_eq!(twist, NQuat::identity());
+        assert!(swing.approx_eq(&flip, margin));
+    }
+
+    #[test]
+    fn test_joint_limits() {
+        use float_cmp::ApproxEq;
+
+        let margin = (1e-12, 8);
+        let axis = Direction3::new_isnormal(0.0_f64, 1.0, 0.0);
+        let x = Direction3::new_isnormal(1.0_f64, 0.0, 0.0);
+        let z = Direction3::new_isnormal(0.0_f64, 0.0, 1.0);
+
+        // cone
+        let swing = NQuat::from_axis_angle(&x, &Angle::from_degrees(80.0));
+        let limited = swing.clamp_cone(Angle::from_degrees(30.0));
+        assert!(limited.approx_eq(&NQuat::from_axis_angle(&x, &Angle::from_degrees(30.0)), margin));
+        assert!(swing.clamp_cone(Angle::from_degrees(90.0)).approx_eq(&swing, margin));
+
+        // twist, either side
+        let twist = NQuat::from_axis_angle(&axis, &Angle::from_degrees(-100.0));
+        let limited = twist.clamp_twist(axis, Angle::from_degrees(-45.0), Angle::from_degrees(10.0));
+        assert!((limited.twist_angle(axis).as_degrees() + 45.0).abs() < 1e-9);
+        let twist = NQuat::from_axis_angle(&axis, &Angle::from_degrees(200.0));
+        assert!((twist.twist_angle(axis).as_degrees() + 160.0).abs() < 1e-9);
+
+        // elliptical: 20 degrees about x, 60 about z
+        let (ax, az) = (Angle::from_degrees(20.0), Angle::from_degrees(60.0));
+        let inside = NQuat::from_axis_angle(&z, &Angle::from_degrees(50.0));
+        assert!(inside.clamp_elliptical_cone(x, z, ax, az).approx_eq(&inside, margin));
+        let outside = NQuat::from_axis_angle(&x, &Angle::from_degrees(50.0));
+        let limited = outside.clamp_elliptical_cone(x, z, ax, az);
+        assert!(limited.approx_eq(&NQuat::from_axis_angle(&x, &Angle::from_degrees(20.0)), margin));
+        let diagonal: Direction3<f64> = From::from(Vec3::new(1.0, 0.0, 1.0));
+        let limited = NQuat::from_axis_angle(&diagonal, &Angle::from_degrees(90.0))
+            .clamp_elliptical_cone(x, z, ax, az);
+        let r = limited.ln() * 2.0;
+        let e = (r.x / ax.as_radians()).powi(2) + (r.z / az.as_radians()).powi(2);
+        assert!((e - 1.0).abs() < 1e-12);
+        assert!((r.x - r.z).abs() < 1e-12);
+
+        // combined
+        let q = NQuat::from_axis_angle(&x, &Angle::from_degrees(60.0))
+            * NQuat::from_axis_angle(&axis, &Angle::from_degrees(90.0));
+        let limited = q.clamp_swing_twist(axis, Angle::from_degrees(45.0),
+                                          Angle::from_degrees(-30.0), Angle::from_degrees(30.0));
+        let expected = NQuat::from_axis_angle(&x, &Angle::from_degrees(45.0))
+            * NQuat::from_axis_angle(&axis, &Angle::from_degrees(30.0));
+        assert!(limited.approx_eq(&expected, margin));
+    }
+
+    #[test]
+    fn test_try_new() {
+        use crate::MathError;
+
+        let half = 0.5_f64;
+        assert!(NQuat::try_new(Vec3::new(half, half, half), half).is_ok());
+        assert_eq!(NQuat::try_new(Vec3::new(1.0_f64, 1.0, 0.0), 0.0),
+                   Err(MathError::NotNormalized));
+        assert_eq!(NQuat::try_new(Vec3::new(0.0_f64, 0.0, 0.0), 0.0),
+                   Err(MathError::NotNormalized));
+        assert_eq!(NQuat::try_new(Vec3::new(f64::NAN, 0.0, 0.0), 1.0),
+                   Err(MathError::NonFinite));
+
+        let q = Quat::new(Vec3::new(0.0_f64, 0.0, 2.0), 0.0);
+        assert_eq!(NQuat::try_from_quat(q), Ok(NQuat::new_isnormal(Vec3::new(0.0, 0.0, 1.0), 0.0)));
+        assert_eq!(NQuat::try_from_quat(Quat::new(Vec3::new(0.0_f64, 0.0, 0.0), 0.0)),
+                   Err(MathError::ZeroLength));
+        assert_eq!(NQuat::try_from_quat(Quat::new(Vec3::new(0.0_f64, 0.0, 0.0), f64::INFINITY)),
+                   Err(MathError::NonFinite));
+
+        // the magnitude of this overflows f32, and of the next underflows,
+        // but both are finite and nonzero
+        let x = NQuat::new_isnormal(Vec3::new(1.0_f32, 0.0, 0.0), 0.0);
+        assert_eq!(NQuat::try_from_quat(Quat::new(Vec3::new(1e20_f32, 0.0, 0.0), 0.0)), Ok(x));
+        assert_eq!(NQuat::try_from_quat(Quat::new(Vec3::new(1e-30_f32, 0.0, 0.0), 0.0)), Ok(x));
+    }
+
+    #[test]
+    fn test_identity_axis_angle() {
+        let (axis, angle) = NQuat::<f64>::identity().as_axis_angle();
+        assert_eq!(axis, Direction3::new_isnormal(1.0, 0.0, 0.0));
+        assert_eq!(angle.as_radians(), 0.0);
+    }
+
+    #[test]
+    fn test_mul_direction() {
+        use float_cmp::ApproxEq;
+
+        let axis: Direction3<f64> = From::from(Vec3::new(1.0, 1.0, 0.0));
+        let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(73.0));
+        let d: Direction3<f64> = From::from(Vec3::new(0.3, -2.0, 1.5));
+        let rotated: Direction3<f64> = q * d;
+        assert!(rotated.approx_eq(&From::from(q * *d), (1e-12, 8)));
+        assert!(((*rotated).magnitude() - 1.0).abs() < 1e-12);
     }
 
     /*
@@ -0,0 +1,385 @@
+
+use num_traits::NumCast;
+use rand::Rng;
+use rand::distributions::{Distribution, Standard};
+use crate::{FullFloat, Vec2, Vec3, Point2, Point3, Direction2, Direction3,
+            Quat, NQuat, Angle};
+
+// NOTE: These distributions are generic over FullFloat, which does not imply
+// that rand can sample F directly.  So we always sample an f64 in [0,1) and
+// cast it.  Given a seeded Rng (e.g. StdRng::seed_from_u64) the results are
+// reproducible.
+
+#[inline]
+fn unit<F: FullFloat, R: Rng + ?Sized>(rng: &mut R) -> F {
+    NumCast::from(rng.gen::<f64>()).unwrap()
+}
+
+#[inline]
+fn two_pi<F: FullFloat>() -> F {
+    let two: F = NumCast::from(2.0_f32).unwrap();
+    two * F::PI()
+}
+
+// ----------------------------------------------------------------------------
+// Angle
+
+/// Angles are sampled uniformly from 0 to 2*PI
+impl<F: FullFloat> Distribution<Angle<F>> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Angle<F> {
+        Angle::from_radians(unit::<F, R>(rng) * two_pi::<F>())
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Directions
+
+/// Directions are sampled uniformly on the unit circle
+impl<F: FullFloat> Distribution<Direction2<F>> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Direction2<F> {
+        let (s, c) = (unit::<F, R>(rng) * two_pi::<F>()).sin_cos();
+        From::from(Vec2::new(c, s))
+    }
+}
+
+/// Directions are sampled uniformly on the unit sphere
+impl<F: FullFloat> Distribution<Direction3<F>> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Direction3<F> {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        // By Archimedes' hat-box theorem, z is uniform on [-1,1]
+        let z = two * unit::<F, R>(rng) - F::one();
+        let (s, c) = (unit::<F, R>(rng) * two_pi::<F>()).sin_cos();
+        let r = (F::one() - z * z).max(F::zero()).sqrt();
+        From::from(Vec3::new(r * c, r * s, z))
+    }
+}
+
+/// Cosine-weighted directions in the hemisphere about a normal
+#[derive(Debug, Clone, Copy)]
+pub struct CosineHemisphere<F> {
+    pub normal: Direction3<F>,
+}
+
+impl<F: FullFloat> CosineHemisphere<F> {
+    pub fn new(normal: Direction3<F>) -> CosineHemisphere<F> {
+        CosineHemisphere { normal }
+    }
+}
+
+impl<F: FullFloat> Distribution<Direction3<F>> for CosineHemisphere<F> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Direction3<F> {
+        // Malley's method: project a uniform disk sample up onto the hemisphere
+        let u = unit::<F, R>(rng);
+        let r = u.sqrt();
+        let (s, c) = (unit::<F, R>(rng) * two_pi::<F>()).sin_cos();
+        let up = (F::one() - u).max(F::zero()).sqrt();
+
+        let n: Vec3<F> = From::from(self.normal);
+        let helper = if n.x.abs() > NumCast::from(0.9_f32).unwrap() {
+            Vec3::new(F::zero(), F::one(), F::zero())
+        } else {
+            Vec3::new(F::one(), F::zero(), F::zero())
+        };
+        let t: Vec3<F> = From::from(Direction3::from(helper.cross(n)));
+        let b = n.cross(t);
+
+        From::from(t * (r * c) + b * (r * s) + n * up)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Rotations
+
+/// Rotations are sampled uniformly over SO(3)
+// Ken Shoemake, "Uniform Random Rotations", Graphics Gems III, p124
+impl<F: FullFloat> Distribution<NQuat<F>> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> NQuat<F> {
+        let u1 = unit::<F, R>(rng);
+        let (s2, c2) = (unit::<F, R>(rng) * two_pi::<F>()).sin_cos();
+        let (s3, c3) = (unit::<F, R>(rng) * two_pi::<F>()).sin_cos();
+        let r1 = (F::one() - u1).sqrt();
+        let r2 = u1.sqrt();
+        let q = Quat::new(Vec3::new(r1 * s2, r1 * c2, r2 * s3), r2 * c3);
+        From::from(q)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Shapes
+
+/// Points uniformly distributed within a disk
+#[derive(Debug, Clone, Copy)]
+pub struct UniformDisk<F> {
+    pub center: Point2<F>,
+    pub radius: F,
+}
+
+impl<F: FullFloat> UniformDisk<F> {
+    pub fn new(center: Point2<F>, radius: F) -> UniformDisk<F> {
+        UniformDisk { center, radius }
+    }
+}
+
+impl<F: FullFloat> Distribution<Point2<F>> for UniformDisk<F> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Point2<F> {
+        let r = self.radius * unit::<F, R>(rng).sqrt();
+        let (s, c) = (unit::<F, R>(rng) * two_pi::<F>()).sin_cos();
+        self.center + Vec2::new(r * c, r * s)
+    }
+}
+
+/// Points uniformly distributed within a ball
+#[derive(Debug, Clone, Copy)]
+pub struct UniformBall<F> {
+    pub center: Point3<F>,
+    pub radius: F,
+}
+
+impl<F: FullFloat> UniformBall<F> {
+    pub fn new(center: Point3<F>, radius: F) -> UniformBall<F> {
+        UniformBall { center, radius }
+    }
+}
+
+impl<F: FullFloat> Distribution<Point3<F>> for UniformBall<F> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Point3<F> {
+        let dir: Direction3<F> = Standard.sample(rng);
+        let dir: Vec3<F> = From::from(dir);
+        let r = self.radius * unit::<F, R>(rng).cbrt();
+        self.center + dir * r
+    }
+}
+
+/// Points uniformly distributed within a triangle
+#[derive(Debug, Clone, Copy)]
+pub struct UniformTriangle<F> {
+    pub a: Point3<F>,
+    pub b: Point3<F>,
+    pub c: Point3<F>,
+}
+
+impl<F: FullFloat> UniformTriangle<F> {
+    pub fn new(a: Point3<F>, b: Point3<F>, c: Point3<F>) -> UniformTriangle<F> {
+        UniformTriangle { a, b, c }
+    }
+}
+
+impl<F: FullFloat> Distribution<Point3<F>> for UniformTriangle<F> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Point3<F> {
+        let mut u = unit::<F, R>(rng);
+        let mut v = unit::<F, R>(rng);
+        // Fold the far half of the parallelogram back onto the triangle
+        if u + v > F::one() {
+            u = F::one() - u;
+            v = F::one() - v;
+        }
+        self.a + (self.b - self.a) * u + (self.c - self.a) * v
+    }
+}
+
+/// Points uniformly distributed within an axis-aligned box
+#[derive(Debug, Clone, Copy)]
+pub struct UniformBox<F> {
+    pub min: Point3<F>,
+    pub max: Point3<F>,
+}
+
+impl<F: FullFloat> UniformBox<F> {
+    pub fn new(min: Point3<F>, max: Point3<F>) -> UniformBox<F> {
+        UniformBox { min, max }
+    }
+}
+
+impl<F: FullFloat> Distribution<Point3<F>> for UniformBox<F> {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Point3<F> {
+        let extent = self.max - self.min;
+        self.min + Vec3::new(extent.x * unit::<F, R>(rng),
+                             extent.y * unit::<F, R>(rng),
+                             extent.z * unit::<F, R>(rng))
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+    use rand::distributions::Distribution;
+    use crate::{Vec3, Point2, Point3, Direction3, NQuat, Angle, Z_AXIS_F64};
+    use super::{CosineHemisphere, UniformDisk, UniformBall, UniformTriangle, UniformBox};
+
+    const N: usize = 20000;
+
+    // Pearson's chi-squared statistic for equally likely bins
+    fn chi_squared(bins: &[usize]) -> f64 {
+        let total: usize = bins.iter().sum();
+        let expected = total as f64 / bins.len() as f64;
+        bins.iter().map(|&b| {
+            let d = b as f64 - expected;
+            d * d / expected
+        }).sum()
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let mut a = StdRng::seed_from_u64(7);
+        let mut b = StdRng::seed_from_u64(7);
+        for _ in 0..100 {
+            let da: Direction3<f32> = a.gen();
+            let db: Direction3<f32> = b.gen();
+            assert_eq!(da, db);
+        }
+    }
+
+    #[test]
+    fn test_angle_uniform() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let mut bins = [0_usize; 16];
+        for _ in 0..N {
+            let a: Angle<f64> = rng.gen();
+            let c = a.as_cycles();
+            assert!((0.0..1.0).contains(&c));
+            bins[(c * 16.0) as usize] += 1;
+        }
+        // 15 degrees of freedom; p=0.001 critical value is 37.7
+        assert!(chi_squared(&bins) < 37.7);
+    }
+
+    #[test]
+    fn test_direction3_uniform() {
+        let mut rng = StdRng::seed_from_u64(2);
+        let mut sum = Vec3::<f64>::zero();
+        let mut zbins = [0_usize; 10];
+        let mut abins = [0_usize; 10];
+        for _ in 0..N {
+            let d: Direction3<f64> = rng.gen();
+            assert!((d.magnitude() - 1.0).abs() < 1e-12);
+            sum += From::from(d);
+            zbins[(((d.z + 1.0) * 5.0) as usize).min(9)] += 1;
+            let a = Angle::of_vector(&d.truncate_z()).as_cycles() + 0.5;
+            abins[((a * 10.0) as usize).min(9)] += 1;
+        }
+        assert!((sum / N as f64).magnitude() < 0.03);
+        // 9 degrees of freedom; p=0.001 critical value is 27.9
+        assert!(chi_squared(&zbins) < 27.9);
+        assert!(chi_squared(&abins) < 27.9);
+    }
+
+    #[test]
+    fn test_cosine_hemisphere() {
+        let mut rng = StdRng::seed_from_u64(3);
+        let normal: Direction3<f64> = From::from(Vec3::new(1.0, 2.0, -0.5));
+        let dist = CosineHemisphere::new(normal);
+        let mut sum = 0.0;
+        let mut bins = [0_usize; 10];
+        for _ in 0..N {
+            let d = dist.sample(&mut rng);
+            let c = d.dot(normal);
+            assert!(c >= -1e-12);
+            sum += c;
+            // cos^2(theta) is uniform on [0,1] for cosine weighting
+            bins[((c * c * 10.0) as usize).min(9)] += 1;
+        }
+        assert!((sum / N as f64 - 2.0 / 3.0).abs() < 0.01);
+        assert!(chi_squared(&bins) < 27.9);
+
+        // normal near the x axis uses the other helper axis
+        let dist = CosineHemisphere::new(Direction3::new_isnormal(1.0, 0.0, 0.0));
+        for _ in 0..100 {
+            assert!(dist.sample(&mut rng).x >= -1e-12);
+        }
+    }
+
+    #[test]
+    fn test_nquat_uniform() {
+        let mut rng = StdRng::seed_from_u64(4);
+        // Rotating a fixed axis by a uniform rotation gives a uniform direction
+        let mut zbins = [0_usize; 10];
+        let mut sum = Vec3::<f64>::zero();
+        for _ in 0..N {
+            let q: NQuat<f64> = rng.gen();
+            let v = q.rotate(From::from(Z_AXIS_F64));
+            sum += v;
+            zbins[(((v.z + 1.0) * 5.0) as usize).min(9)] += 1;
+        }
+        assert!((sum / N as f64).magnitude() < 0.03);
+        assert!(chi_squared(&zbins) < 27.9);
+    }
+
+    #[test]
+    fn test_disk_uniform() {
+        let mut rng = StdRng::seed_from_u64(5);
+        let center = Point2::new(3.0_f64, -2.0);
+        let dist = UniformDisk::new(center, 2.0);
+        // Equal-area rings have radii 2*sqrt(k/10)
+        let mut bins = [0_usize; 10];
+        for _ in 0..N {
+            let p = dist.sample(&mut rng);
+            let r = (p - center).magnitude();
+            assert!(r <= 2.0);
+            bins[((r * r / 4.0 * 10.0) as usize).min(9)] += 1;
+        }
+        assert!(chi_squared(&bins) < 27.9);
+    }
+
+    #[test]
+    fn test_ball_uniform() {
+        let mut rng = StdRng::seed_from_u64(6);
+        let center = Point3::new(1.0_f64, 1.0, 1.0);
+        let dist = UniformBall::new(center, 3.0);
+        // Equal-volume shells have radii 3*cbrt(k/10)
+        let mut bins = [0_usize; 10];
+        for _ in 0..N {
+            let p = dist.sample(&mut rng);
+            let r = (p - center).magnitude() / 3.0;
+            assert!(r <= 1.0 + 1e-12);
+            bins[((r * r * r * 10.0) as usize).min(9)] += 1;
+        }
+        assert!(chi_squared(&bins) < 27.9);
+    }
+
+    #[test]
+    fn test_triangle_uniform() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let a = Point3::new(0.0_f64, 0.0, 0.0);
+        let b = Point3::new(4.0_f64, 0.0, 0.0);
+        let c = Point3::new(0.0_f64, 4.0, 0.0);
+        let dist = UniformTriangle::new(a, b, c);
+        // The midpoints split the triangle into 4 congruent triangles
+        let mut bins = [0_usize; 4];
+        for _ in 0..N {
+            let p = dist.sample(&mut rng);
+            assert!(p.x >= 0.0 && p.y >= 0.0 && p.x + p.y <= 4.0 + 1e-12);
+            assert_eq!(p.z, 0.0);
+            let i = if p.x > 2.0 { 0 }
+                    else if p.y > 2.0 { 1 }
+                    else if p.x + p.y < 2.0 { 2 }
+                    else { 3 };
+            bins[i] += 1;
+        }
+        // 3 degrees of freedom; p=0.001 critical value is 16.3
+        assert!(chi_squared(&bins) < 16.3);
+    }
+
+    #[test]
+    fn test_box_uniform() {
+        let mut rng = StdRng::seed_from_u64(8);
+        let min = Point3::new(-1.0_f32, 2.0, 5.0);
+        let max = Point3::new(1.0_f32, 6.0, 6.0);
+        let dist = UniformBox::new(min, max);
+        let mut bins = [0_usize; 8];
+        for _ in 0..N {
+            let p = dist.sample(&mut rng);
+            assert!(p.x >= -1.0 && p.x <= 1.0);
+            assert!(p.y >= 2.0 && p.y <= 6.0);
+            assert!(p.z >= 5.0 && p.z <= 6.0);
+            let i = (p.x > 0.0) as usize
+                + 2 * (p.y > 4.0) as usize
+                + 4 * (p.z > 5.5) as usize;
+            bins[i] += 1;
+        }
+        // 7 degrees of freedom; p=0.001 critical value is 24.3
+        assert!(chi_squared(&bins) < 24.3);
+    }
+}
@@ -0,0 +1,255 @@
+
+use std::ops::{Mul, Div};
+use serde::{Serialize, Deserialize};
+use float_cmp::ApproxEq;
+use crate::{FullFloat, Vec2, Point2, Direction2, Mat2, Angle};
+
+/// A rotation in 2 dimensions, stored as a unit complex number
+/// (cos θ + i sin θ)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub struct Rot2<F> {
+    c: F,
+    s: F,
+}
+
+impl<F: FullFloat> Rot2<F> {
+    /// Create from the cosine and sine of the angle, which must already
+    /// form a unit complex number
+    pub fn new_isnormal(c: F, s: F) -> Rot2<F> {
+        let margin: F::Margin = Default::default();
+        assert!((c * c + s * s).sqrt().approx_eq(F::one(), margin));
+        Rot2 { c, s }
+    }
+}
+
+impl<F: FullFloat> Rot2<F> {
+    pub fn identity() -> Rot2<F> {
+        Rot2 { c: F::one(), s: F::zero() }
+    }
+}
+
+impl<F: FullFloat> Default for Rot2<F> {
+    fn default() -> Rot2<F> {
+        Rot2::identity()
+    }
+}
+
+impl<F: FullFloat> Rot2<F> {
+    /// Counter-clockwise rotation by `theta`
+    pub fn from_angle(theta: Angle<F>) -> Rot2<F> {
+        let (s, c) = theta.as_radians().sin_cos();
+        Rot2 { c, s }
+    }
+}
+
+impl<F: FullFloat> Rot2<F> {
+    /// The angle, within -PI to PI
+    pub fn angle(&self) -> Angle<F> {
+        Angle::from_radians(self.s.atan2(self.c))
+    }
+
+    #[inline]
+    pub fn cos(&self) -> F {
+        self.c
+    }
+
+    #[inline]
+    pub fn sin(&self) -> F {
+        self.s
+    }
+}
+
+impl<F: FullFloat> Rot2<F> {
+    /// The rotation taking `start` onto `end`
+    pub fn from_directions(start: Direction2<F>, end: Direction2<F>) -> Rot2<F> {
+        // end * conj(start)
+        Rot2::normalize(start.x * end.x + start.y * end.y,
+                        start.x * end.y - start.y * end.x)
+    }
+}
+
+impl<F: FullFloat> Rot2<F> {
+    // Renormalize to counter drift after repeated composition
+    fn normalize(c: F, s: F) -> Rot2<F> {
+        let mag = (c * c + s * s).sqrt();
+        Rot2 { c: c / mag, s: s / mag }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Compose, inverse
+
+impl<F: FullFloat> Mul for Rot2<F> {
+    type Output = Rot2<F>;
+
+    /// Composition (rotations in 2D commute)
+    fn mul(self, rhs: Rot2<F>) -> Rot2<F> {
+        Rot2::normalize(self.c * rhs.c - self.s * rhs.s,
+                        self.c * rhs.s + self.s * rhs.c)
+    }
+}
+
+impl<F: FullFloat> Rot2<F> {
+    pub fn inverse(&self) -> Rot2<F> {
+        Rot2 { c: self.c, s: -self.s }
+    }
+}
+
+impl<F: FullFloat> Div for Rot2<F> {
+    type Output = Rot2<F>;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Rot2<F>) -> Rot2<F> {
+        self * rhs.inverse()
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Rotate vectors, points and directions
+
+impl<F: FullFloat> Rot2<F> {
+    pub fn rotate(&self, v: Vec2<F>) -> Vec2<F> {
+        Vec2::new(self.c * v.x - self.s * v.y,
+                  self.s * v.x + self.c * v.y)
+    }
+}
+
+impl<F: FullFloat> Mul<Vec2<F>> for Rot2<F> {
+    type Output = Vec2<F>;
+
+    fn mul(self, rhs: Vec2<F>) -> Vec2<F> {
+        self.rotate(rhs)
+    }
+}
+
+/// Rotates about the origin
+impl<F: FullFloat> Mul<Point2<F>> for Rot2<F> {
+    type Output = Point2<F>;
+
+    fn mul(self, rhs: Point2<F>) -> Point2<F> {
+        Point2(self.rotate(rhs.0))
+    }
+}
+
+impl<F: FullFloat> Mul<Direction2<F>> for Rot2<F> {
+    type Output = Direction2<F>;
+
+    fn mul(self, rhs: Direction2<F>) -> Direction2<F> {
+        From::from(self.rotate(*rhs))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Interpolation
+
+impl<F: FullFloat> Rot2<F> {
+    /// Interpolate at constant angular speed along the shorter arc from
+    /// `self` (t = 0) to `other` (t = 1)
+    pub fn slerp(&self, other: &Rot2<F>, t: F) -> Rot2<F> {
+        let delta = (*other / *self).angle();
+        *self * Rot2::from_angle(delta * t)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Conversions
+
+impl<F: FullFloat> From<Rot2<F>> for Mat2<F> {
+    fn from(r: Rot2<F>) -> Mat2<F> {
+        Mat2::new(r.c, -r.s,
+                  r.s, r.c)
+    }
+}
+
+impl<F: FullFloat> From<Angle<F>> for Rot2<F> {
+    fn from(a: Angle<F>) -> Rot2<F> {
+        Rot2::from_angle(a)
+    }
+}
+
+impl<F: FullFloat> From<Rot2<F>> for Angle<F> {
+    fn from(r: Rot2<F>) -> Angle<F> {
+        r.angle()
+    }
+}
+
+impl From<Rot2<f32>> for Rot2<f64> {
+    fn from(r: Rot2<f32>) -> Rot2<f64> {
+        Rot2 { c: r.c as f64, s: r.s as f64 }
+    }
+}
+
+impl From<Rot2<f64>> for Rot2<f32> {
+    fn from(r: Rot2<f64>) -> Rot2<f32> {
+        Rot2 { c: r.c as f32, s: r.s as f32 }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ApproxEq
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Rot2<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.c.approx_eq(other.c, margin)
+            && self.s.approx_eq(other.s, margin)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Vec2, Point2, Direction2, Mat2, Angle};
+    use super::Rot2;
+
+    const MARGIN: (f64, i64) = (1e-12, 8);
+
+    #[test]
+    fn test_rotate() {
+        let r = Rot2::<f64>::from_angle(Angle::from_degrees(90.0));
+        assert!(r.rotate(Vec2::new(1.0, 0.0)).approx_eq(&Vec2::new(0.0, 1.0), MARGIN));
+        let p = r * Point2::new(0.0, 2.0);
+        assert!(p.approx_eq(&Point2::new(-2.0, 0.0), MARGIN));
+        let d = r * Direction2::new_isnormal(0.6, 0.8);
+        assert!(d.approx_eq(&Direction2::new_isnormal(-0.8, 0.6), MARGIN));
+
+        let m: Mat2<f64> = From::from(r);
+        let v = Vec2::new(3.0, -1.5);
+        assert!((m * v).approx_eq(&r.rotate(v), MARGIN));
+        assert!(m.approx_eq(&Mat2::from_angle(Angle::from_degrees(90.0)), MARGIN));
+    }
+
+    #[test]
+    fn test_compose_inverse() {
+        let a = Rot2::<f64>::from_angle(Angle::from_degrees(30.0));
+        let b = Rot2::<f64>::from_angle(Angle::from_degrees(100.0));
+        assert!((a * b).angle().approx_eq(&Angle::from_degrees(130.0), MARGIN));
+        assert!((a * a.inverse()).approx_eq(&Rot2::identity(), MARGIN));
+        assert!((b / a).angle().approx_eq(&Angle::from_degrees(70.0), MARGIN));
+
+        // angles wrap into -180..180
+        let c = Rot2::<f64>::from_angle(Angle::from_degrees(270.0));
+        assert!(c.angle().approx_eq(&Angle::from_degrees(-90.0), MARGIN));
+
+        let start = Direction2::new_isnormal(1.0_f64, 0.0);
+        let end = Direction2::new_isnormal(0.0, -1.0);
+        let r = Rot2::from_directions(start, end);
+        assert!(r.angle().approx_eq(&Angle::from_degrees(-90.0), MARGIN));
+    }
+
+    #[test]
+    fn test_slerp() {
+        let a = Rot2::<f64>::from_angle(Angle::from_degrees(170.0));
+        let b = Rot2::<f64>::from_angle(Angle::from_degrees(-170.0));
+        assert!(a.slerp(&b, 0.0).approx_eq(&a, MARGIN));
+        assert!(a.slerp(&b, 1.0).approx_eq(&b, MARGIN));
+        // the short way round, through 180
+        let mid = a.slerp(&b, 0.5);
+        assert!(mid.approx_eq(&Rot2::from_angle(Angle::from_degrees(180.0)), MARGIN));
+    }
+}
@@ -0,0 +1,363 @@
+
+//! Rotors and bivectors from 3D geometric algebra.
+//!
+//! A bivector is an oriented plane with a magnitude (an area), the result
+//! of the wedge product of two vectors.  A rotor is a scalar plus a
+//! bivector, and rotates vectors within its plane by the sandwich product
+//! `R v R~`.  Rotors are isomorphic to unit quaternions, and convert to and
+//! from `NQuat` exactly.
+
+use std::ops::{Add, Sub, Mul, Neg};
+use serde::{Serialize, Deserialize};
+use num_traits::NumCast;
+use float_cmp::ApproxEq;
+use crate::{FullFloat, Vec3, Direction3, Mat3, Quat, NQuat, Angle};
+
+/// A bivector in 3 dimensions, with components on the xy, xz and yz planes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub struct Bivector3<F> {
+    pub xy: F,
+    pub xz: F,
+    pub yz: F,
+}
+
+impl<F: FullFloat> Bivector3<F> {
+    #[inline]
+    pub fn new(xy: F, xz: F, yz: F) -> Bivector3<F> {
+        Bivector3 { xy, xz, yz }
+    }
+}
+
+impl<F: FullFloat> Bivector3<F> {
+    #[inline]
+    pub fn zero() -> Bivector3<F> {
+        Bivector3::new(F::zero(), F::zero(), F::zero())
+    }
+}
+
+impl<F: FullFloat> Vec3<F> {
+    /// The wedge (outer) product, the plane spanned by the two vectors
+    pub fn wedge(&self, rhs: Vec3<F>) -> Bivector3<F> {
+        Bivector3::new(self.x * rhs.y - self.y * rhs.x,
+                       self.x * rhs.z - self.z * rhs.x,
+                       self.y * rhs.z - self.z * rhs.y)
+    }
+}
+
+impl<F: FullFloat> Bivector3<F> {
+    pub fn squared_magnitude(&self) -> F {
+        self.xy * self.xy + self.xz * self.xz + self.yz * self.yz
+    }
+
+    pub fn magnitude(&self) -> F {
+        self.squared_magnitude().sqrt()
+    }
+
+    /// The normal of the plane, such that `a.wedge(b).dual() == a.cross(b)`
+    pub fn dual(&self) -> Vec3<F> {
+        Vec3::new(self.yz, -self.xz, self.xy)
+    }
+}
+
+impl<F: FullFloat> Add for Bivector3<F> {
+    type Output = Bivector3<F>;
+
+    fn add(self, rhs: Bivector3<F>) -> Bivector3<F> {
+        Bivector3::new(self.xy + rhs.xy, self.xz + rhs.xz, self.yz + rhs.yz)
+    }
+}
+
+impl<F: FullFloat> Sub for Bivector3<F> {
+    type Output = Bivector3<F>;
+
+    fn sub(self, rhs: Bivector3<F>) -> Bivector3<F> {
+        Bivector3::new(self.xy - rhs.xy, self.xz - rhs.xz, self.yz - rhs.yz)
+    }
+}
+
+impl<F: FullFloat> Mul<F> for Bivector3<F> {
+    type Output = Bivector3<F>;
+
+    fn mul(self, rhs: F) -> Bivector3<F> {
+        Bivector3::new(self.xy * rhs, self.xz * rhs, self.yz * rhs)
+    }
+}
+
+impl<F: FullFloat> Neg for Bivector3<F> {
+    type Output = Bivector3<F>;
+
+    fn neg(self) -> Bivector3<F> {
+        Bivector3::new(-self.xy, -self.xz, -self.yz)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A rotor in 3 dimensions (normalized), a scalar plus a bivector
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Serialize, Deserialize)]
+pub struct Rotor3<F> {
+    s: F,
+    b: Bivector3<F>,
+}
+
+impl<F: FullFloat> Rotor3<F> {
+    pub fn new_isnormal(s: F, b: Bivector3<F>) -> Rotor3<F> {
+        let margin: F::Margin = Default::default();
+        assert!((s * s + b.squared_magnitude()).sqrt().approx_eq(F::one(), margin));
+        Rotor3 { s, b }
+    }
+}
+
+impl<F: FullFloat> Rotor3<F> {
+    // Scale onto the unit hypersphere
+    fn normalize(s: F, b: Bivector3<F>) -> Rotor3<F> {
+        let mag = (s * s + b.squared_magnitude()).sqrt();
+        Rotor3 { s: s / mag, b: b * (F::one() / mag) }
+    }
+}
+
+impl<F: FullFloat> Rotor3<F> {
+    pub fn identity() -> Rotor3<F> {
+        Rotor3 { s: F::one(), b: Bivector3::zero() }
+    }
+}
+
+impl<F: FullFloat> Default for Rotor3<F> {
+    fn default() -> Rotor3<F> {
+        Rotor3::identity()
+    }
+}
+
+impl<F: FullFloat> Rotor3<F> {
+    #[inline]
+    pub fn scalar(&self) -> F {
+        self.s
+    }
+
+    #[inline]
+    pub fn bivector(&self) -> Bivector3<F> {
+        self.b
+    }
+}
+
+impl<F: FullFloat> Rotor3<F> {
+    /// Rotation by `angle` within `plane`, turning the plane's first vector
+    /// towards its second (so `x.wedge(y)` turns X towards Y).  Panics on a
+    /// zero plane.
+    pub fn from_plane_angle(plane: &Bivector3<F>, angle: &Angle<F>) -> Rotor3<F> {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        let mag = plane.magnitude();
+        assert!(mag > F::zero());
+        let (s, c) = (angle.as_radians() / two).sin_cos();
+        Rotor3::normalize(c, *plane * (-s / mag))
+    }
+}
+
+impl<F: FullFloat> Rotor3<F> {
+    /// The rotation taking the direction of `from` to the direction of `to`
+    /// in the plane they span.  If they are opposite, any plane containing
+    /// them is used.
+    pub fn from_vectors(from: Vec3<F>, to: Vec3<F>) -> Rotor3<F> {
+        let from: Vec3<F> = *Direction3::from(from);
+        let to: Vec3<F> = *Direction3::from(to);
+        let s = F::one() + to.dot(from);
+        let b = to.wedge(from);
+        let tolerance: F = NumCast::from(1e-6_f32).unwrap();
+        if s <= tolerance && b.magnitude() <= tolerance {
+            // Opposite: a half turn in any plane containing `from`
+            let other = if from.x.abs() < from.z.abs() {
+                Vec3::new(F::one(), F::zero(), F::zero())
+            } else {
+                Vec3::new(F::zero(), F::zero(), F::one())
+            };
+            let angle = Angle::from_radians(F::PI());
+            return Rotor3::from_plane_angle(&from.wedge(other), &angle);
+        }
+        Rotor3::normalize(s, b)
+    }
+}
+
+impl<F: FullFloat> Rotor3<F> {
+    /// The reverse, which undoes the rotation
+    pub fn reverse(&self) -> Rotor3<F> {
+        Rotor3 { s: self.s, b: -self.b }
+    }
+}
+
+/// Composition: `p * q` rotates by `q` and then by `p`
+impl<F: FullFloat> Mul for Rotor3<F> {
+    type Output = Rotor3<F>;
+
+    fn mul(self, q: Rotor3<F>) -> Rotor3<F> {
+        let p = self;
+        let s = p.s * q.s - p.b.xy * q.b.xy - p.b.xz * q.b.xz - p.b.yz * q.b.yz;
+        let b = Bivector3::new(
+            p.s * q.b.xy + p.b.xy * q.s - p.b.xz * q.b.yz + p.b.yz * q.b.xz,
+            p.s * q.b.xz + p.b.xz * q.s + p.b.xy * q.b.yz - p.b.yz * q.b.xy,
+            p.s * q.b.yz + p.b.yz * q.s - p.b.xy * q.b.xz + p.b.xz * q.b.xy);
+        Rotor3::normalize(s, b)
+    }
+}
+
+impl<F: FullFloat> Rotor3<F> {
+    /// Rotate a vector, `R v R~`
+    pub fn rotate(&self, v: Vec3<F>) -> Vec3<F> {
+        // first, R v: a vector plus a trivector
+        let q = Vec3::new(self.s * v.x + v.y * self.b.xy + v.z * self.b.xz,
+                          self.s * v.y - v.x * self.b.xy + v.z * self.b.yz,
+                          self.s * v.z - v.x * self.b.xz - v.y * self.b.yz);
+        let t = v.x * self.b.yz - v.y * self.b.xz + v.z * self.b.xy;
+        // then (R v) R~, whose trivector part vanishes
+        Vec3::new(self.s * q.x + q.y * self.b.xy + q.z * self.b.xz + t * self.b.yz,
+                  self.s * q.y - q.x * self.b.xy - t * self.b.xz + q.z * self.b.yz,
+                  self.s * q.z + t * self.b.xy - q.x * self.b.xz - q.y * self.b.yz)
+    }
+}
+
+impl<F: FullFloat> Mul<Vec3<F>> for Rotor3<F> {
+    type Output = Vec3<F>;
+
+    fn mul(self, rhs: Vec3<F>) -> Vec3<F> {
+        self.rotate(rhs)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Conversions
+//
+// Quaternion i, j, k correspond to the bivectors -yz, xz and -xy.
+
+impl<F: FullFloat> From<NQuat<F>> for Rotor3<F> {
+    fn from(q: NQuat<F>) -> Rotor3<F> {
+        let q: Quat<F> = From::from(q);
+        Rotor3 { s: q.w, b: Bivector3::new(-q.v.z, q.v.y, -q.v.x) }
+    }
+}
+
+impl<F: FullFloat> From<Rotor3<F>> for NQuat<F> {
+    fn from(r: Rotor3<F>) -> NQuat<F> {
+        From::from(Quat::new(Vec3::new(-r.b.yz, r.b.xz, -r.b.xy), r.s))
+    }
+}
+
+impl<F: FullFloat> From<Rotor3<F>> for Mat3<F> {
+    fn from(r: Rotor3<F>) -> Mat3<F> {
+        let q: NQuat<F> = From::from(r);
+        From::from(q)
+    }
+}
+
+impl<F: FullFloat> From<Mat3<F>> for Rotor3<F> {
+    fn from(m: Mat3<F>) -> Rotor3<F> {
+        let q: NQuat<F> = From::from(m);
+        From::from(q)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// ApproxEq
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Bivector3<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.xy.approx_eq(other.xy, margin)
+            && self.xz.approx_eq(other.xz, margin)
+            && self.yz.approx_eq(other.yz, margin)
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Rotor3<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.s.approx_eq(other.s, margin)
+            && self.b.approx_eq(&other.b, margin)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Vec3, Mat3, NQuat, Angle, Direction3};
+    use super::{Bivector3, Rotor3};
+
+    const MARGIN: (f64, i64) = (1e-12, 8);
+
+    #[test]
+    fn test_wedge() {
+        let a = Vec3::<f64>::new(1.0, 2.0, 3.0);
+        let b = Vec3::<f64>::new(-2.0, 0.5, 4.0);
+        assert_eq!(a.wedge(b), -b.wedge(a));
+        assert_eq!(a.wedge(a), Bivector3::zero());
+        assert_eq!(a.wedge(b).dual(), a.cross(b));
+        let x = Vec3::<f64>::new(1.0, 0.0, 0.0);
+        let y = Vec3::<f64>::new(0.0, 1.0, 0.0);
+        assert_eq!(x.wedge(y), Bivector3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn test_plane_angle() {
+        let x = Vec3::<f64>::new(1.0, 0.0, 0.0);
+        let y = Vec3::<f64>::new(0.0, 1.0, 0.0);
+        let z = Vec3::<f64>::new(0.0, 0.0, 1.0);
+        let r = Rotor3::from_plane_angle(&x.wedge(y), &Angle::from_degrees(90.0));
+        assert!(r.rotate(x).approx_eq(&y, MARGIN));
+        assert!(r.rotate(z).approx_eq(&z, MARGIN));
+        let r = Rotor3::from_plane_angle(&y.wedge(z), &Angle::from_degrees(90.0));
+        assert!((r * y).approx_eq(&z, MARGIN));
+    }
+
+    #[test]
+    fn test_from_vectors() {
+        let a = Vec3::<f64>::new(1.0, 2.0, -0.5);
+        let b = Vec3::<f64>::new(-3.0, 0.25, 1.0);
+        let r = Rotor3::from_vectors(a, b);
+        assert!(r.rotate(a / a.magnitude()).approx_eq(&(b / b.magnitude()), MARGIN));
+
+        assert!(Rotor3::from_vectors(a, a * 2.0).approx_eq(&Rotor3::identity(), MARGIN));
+
+        let r = Rotor3::from_vectors(a, -a);
+        assert!(r.rotate(a).approx_eq(&-a, (1e-12, 16)));
+    }
+
+    #[test]
+    fn test_compose_reverse() {
+        let p = Rotor3::from_vectors(Vec3::new(1.0_f64, 0.0, 0.0), Vec3::new(0.0, 1.0, 1.0));
+        let q = Rotor3::from_vectors(Vec3::new(0.5_f64, 1.0, 0.0), Vec3::new(0.0, -1.0, 2.0));
+        let v = Vec3::new(0.3, -0.7, 1.1);
+        assert!((p * q).rotate(v).approx_eq(&p.rotate(q.rotate(v)), MARGIN));
+        assert!(p.reverse().rotate(p.rotate(v)).approx_eq(&v, MARGIN));
+        assert!((p * p.reverse()).approx_eq(&Rotor3::identity(), MARGIN));
+    }
+
+    #[test]
+    fn test_conversions() {
+        let axis: Direction3<f64> = From::from(Vec3::new(0.3, -1.0, 0.6));
+        let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(75.0));
+        let r: Rotor3<f64> = From::from(q);
+        let v = Vec3::new(1.0, 2.0, 3.0);
+        assert!(r.rotate(v).approx_eq(&q.rotate(v), MARGIN));
+
+        let back: NQuat<f64> = From::from(r);
+        assert!(back.approx_eq(&q, MARGIN));
+
+        let m: Mat3<f64> = From::from(r);
+        assert!((m * v).approx_eq(&r.rotate(v), MARGIN));
+        let r2: Rotor3<f64> = From::from(m);
+        assert!(r2.rotate(v).approx_eq(&r.rotate(v), MARGIN));
+
+        // composition agrees with quaternion multiplication
+        let q2 = NQuat::from_axis_angle(&Direction3::new_isnormal(0.0, 0.0, 1.0),
+                                        &Angle::from_degrees(-40.0));
+        let r2: Rotor3<f64> = From::from(q2);
+        let composed: NQuat<f64> = From::from(r * r2);
+        assert!(composed.approx_eq(&(q * q2), MARGIN));
+    }
+}
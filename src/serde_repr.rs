@@ -0,0 +1,630 @@
+
+//! Alternative serde representations, for use with `#[serde(with = ...)]`.
+//!
+//! The derived serde impls mirror the in-memory layout, which is verbose to
+//! write by hand (e.g. a `Mat4` becomes four nested column structs).  These
+//! modules trade that for compact, human-friendly forms:
+//!
+//! ```ignore
+//! #[derive(Serialize, Deserialize)]
+//! struct Spawn {
+//!     #[serde(with = "siege_math::serde_repr::array")]
+//!     at: Point3<f32>,                      // [1.0, 2.0, 3.0]
+//!     #[serde(with = "siege_math::serde_repr::degrees")]
+//!     heading: Angle<f32>,                  // 90.0
+//!     #[serde(with = "siege_math::serde_repr::euler")]
+//!     facing: NQuat<f32>,                   // [0.0, 0.0, 90.0]
+//! }
+//! ```
+//!
+//! Deserialization rejects non-finite numbers, and directions and unit
+//! quaternions are renormalized (rejecting zero length).
+
+use serde::{Serialize, Deserialize, Serializer, Deserializer};
+use serde::de::{DeserializeOwned, Error};
+use num_traits::NumCast;
+use crate::{FullFloat, Vec2, Vec3, Vec4, Point2, Point3, Direction2, Direction3,
+            Mat2, Mat3, Mat4, Quat, NQuat, Angle, MathError};
+
+fn check_finite<F: FullFloat>(values: &[F]) -> Result<(), &'static str> {
+    if values.iter().all(|v| v.is_finite()) {
+        Ok(())
+    } else {
+        Err("non-finite value")
+    }
+}
+
+fn nquat_from<F: FullFloat>(q: Quat<F>) -> Result<NQuat<F>, &'static str> {
+    NQuat::try_from_quat(q).map_err(MathError::message)
+}
+
+// ----------------------------------------------------------------------------
+// Arrays
+
+/// Types which can be represented as a flat array of numbers
+pub trait ArrayRepr: Sized {
+    type Array: Serialize + DeserializeOwned;
+
+    fn to_array(&self) -> Self::Array;
+    fn from_array(a: Self::Array) -> Result<Self, &'static str>;
+}
+
+/// Serializes vectors, points and directions as `[x, y, z]`, quaternions as
+/// `[x, y, z, w]`, and matrices as a flat array in row-major order.
+pub mod array {
+    use super::*;
+
+    pub fn serialize<T: ArrayRepr, S: Serializer>(value: &T, serializer: S)
+                                                  -> Result<S::Ok, S::Error>
+    {
+        value.to_array().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: ArrayRepr, D: Deserializer<'de>>(deserializer: D)
+                                                                -> Result<T, D::Error>
+    {
+        let a = T::Array::deserialize(deserializer)?;
+        T::from_array(a).map_err(D::Error::custom)
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Vec2<F> {
+    type Array = [F; 2];
+
+    fn to_array(&self) -> [F; 2] {
+        [self.x, self.y]
+    }
+
+    fn from_array(a: [F; 2]) -> Result<Vec2<F>, &'static str> {
+        check_finite(&a)?;
+        Ok(Vec2::new(a[0], a[1]))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Vec3<F> {
+    type Array = [F; 3];
+
+    fn to_array(&self) -> [F; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    fn from_array(a: [F; 3]) -> Result<Vec3<F>, &'static str> {
+        check_finite(&a)?;
+        Ok(Vec3::new(a[0], a[1], a[2]))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Vec4<F> {
+    type Array = [F; 4];
+
+    fn to_array(&self) -> [F; 4] {
+        [self.x, self.y, self.z, self.w]
+    }
+
+    fn from_array(a: [F; 4]) -> Result<Vec4<F>, &'static str> {
+        check_finite(&a)?;
+        Ok(Vec4::new(a[0], a[1], a[2], a[3]))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Point2<F> {
+    type Array = [F; 2];
+
+    fn to_array(&self) -> [F; 2] {
+        self.0.to_array()
+    }
+
+    fn from_array(a: [F; 2]) -> Result<Point2<F>, &'static str> {
+        Ok(Point2(Vec2::from_array(a)?))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Point3<F> {
+    type Array = [F; 3];
+
+    fn to_array(&self) -> [F; 3] {
+        self.0.to_array()
+    }
+
+    fn from_array(a: [F; 3]) -> Result<Point3<F>, &'static str> {
+        Ok(Point3(Vec3::from_array(a)?))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Direction2<F> {
+    type Array = [F; 2];
+
+    fn to_array(&self) -> [F; 2] {
+        [self.x, self.y]
+    }
+
+    fn from_array(a: [F; 2]) -> Result<Direction2<F>, &'static str> {
+        Direction2::try_from_vec(Vec2::new(a[0], a[1]))
+            .map_err(MathError::message)
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Direction3<F> {
+    type Array = [F; 3];
+
+    fn to_array(&self) -> [F; 3] {
+        [self.x, self.y, self.z]
+    }
+
+    fn from_array(a: [F; 3]) -> Result<Direction3<F>, &'static str> {
+        Direction3::try_from_vec(Vec3::new(a[0], a[1], a[2]))
+            .map_err(MathError::message)
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Quat<F> {
+    type Array = [F; 4];
+
+    fn to_array(&self) -> [F; 4] {
+        [self.v.x, self.v.y, self.v.z, self.w]
+    }
+
+    fn from_array(a: [F; 4]) -> Result<Quat<F>, &'static str> {
+        check_finite(&a)?;
+        Ok(Quat::new(Vec3::new(a[0], a[1], a[2]), a[3]))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for NQuat<F> {
+    type Array = [F; 4];
+
+    fn to_array(&self) -> [F; 4] {
+        let q: Quat<F> = From::from(*self);
+        q.to_array()
+    }
+
+    fn from_array(a: [F; 4]) -> Result<NQuat<F>, &'static str> {
+        nquat_from(Quat::new(Vec3::new(a[0], a[1], a[2]), a[3]))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Mat2<F> {
+    type Array = [F; 4];
+
+    fn to_array(&self) -> [F; 4] {
+        [self.x.x, self.y.x,
+         self.x.y, self.y.y]
+    }
+
+    fn from_array(a: [F; 4]) -> Result<Mat2<F>, &'static str> {
+        check_finite(&a)?;
+        Ok(Mat2::new(a[0], a[1],
+                     a[2], a[3]))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Mat3<F> {
+    type Array = [F; 9];
+
+    fn to_array(&self) -> [F; 9] {
+        [self.x.x, self.y.x, self.z.x,
+         self.x.y, self.y.y, self.z.y,
+         self.x.z, self.y.z, self.z.z]
+    }
+
+    fn from_array(a: [F; 9]) -> Result<Mat3<F>, &'static str> {
+        check_finite(&a)?;
+        Ok(Mat3::new(a[0], a[1], a[2],
+                     a[3], a[4], a[5],
+                     a[6], a[7], a[8]))
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> ArrayRepr for Mat4<F> {
+    type Array = [F; 16];
+
+    fn to_array(&self) -> [F; 16] {
+        [self.x.x, self.y.x, self.z.x, self.p.x,
+         self.x.y, self.y.y, self.z.y, self.p.y,
+         self.x.z, self.y.z, self.z.z, self.p.z,
+         self.x.w, self.y.w, self.z.w, self.p.w]
+    }
+
+    fn from_array(a: [F; 16]) -> Result<Mat4<F>, &'static str> {
+        check_finite(&a)?;
+        Ok(Mat4::new(a[0], a[1], a[2], a[3],
+                     a[4], a[5], a[6], a[7],
+                     a[8], a[9], a[10], a[11],
+                     a[12], a[13], a[14], a[15]))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Named fields
+
+#[derive(Serialize, Deserialize)]
+pub struct Xy<F> { x: F, y: F }
+
+#[derive(Serialize, Deserialize)]
+pub struct Xyz<F> { x: F, y: F, z: F }
+
+#[derive(Serialize, Deserialize)]
+pub struct Xyzw<F> { x: F, y: F, z: F, w: F }
+
+/// Serializes vectors, points and directions as flat `{x, y, z}` structs,
+/// and quaternions as `{x, y, z, w}` (rather than the nested `{v, w}`).
+pub mod named {
+    use super::*;
+
+    pub fn serialize<T: ArrayRepr, S: Serializer>(value: &T, serializer: S)
+                                                  -> Result<S::Ok, S::Error>
+        where T::Array: Named
+    {
+        value.to_array().to_named().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, T: ArrayRepr, D: Deserializer<'de>>(deserializer: D)
+                                                                -> Result<T, D::Error>
+        where T::Array: Named
+    {
+        let n = <T::Array as Named>::Named::deserialize(deserializer)?;
+        T::from_array(Named::from_named(n)).map_err(D::Error::custom)
+    }
+}
+
+/// Arrays which have a named-field form
+pub trait Named: Sized {
+    type Named: Serialize + DeserializeOwned;
+
+    fn to_named(&self) -> Self::Named;
+    fn from_named(n: Self::Named) -> Self;
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> Named for [F; 2] {
+    type Named = Xy<F>;
+
+    fn to_named(&self) -> Xy<F> {
+        Xy { x: self[0], y: self[1] }
+    }
+
+    fn from_named(n: Xy<F>) -> [F; 2] {
+        [n.x, n.y]
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> Named for [F; 3] {
+    type Named = Xyz<F>;
+
+    fn to_named(&self) -> Xyz<F> {
+        Xyz { x: self[0], y: self[1], z: self[2] }
+    }
+
+    fn from_named(n: Xyz<F>) -> [F; 3] {
+        [n.x, n.y, n.z]
+    }
+}
+
+impl<F: FullFloat + Serialize + DeserializeOwned> Named for [F; 4] {
+    type Named = Xyzw<F>;
+
+    fn to_named(&self) -> Xyzw<F> {
+        Xyzw { x: self[0], y: self[1], z: self[2], w: self[3] }
+    }
+
+    fn from_named(n: Xyzw<F>) -> [F; 4] {
+        [n.x, n.y, n.z, n.w]
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Angles
+
+/// Serializes an `Angle` as a number of degrees
+pub mod degrees {
+    use super::*;
+
+    pub fn serialize<F, S>(value: &Angle<F>, serializer: S) -> Result<S::Ok, S::Error>
+        where F: FullFloat + Serialize, S: Serializer
+    {
+        value.as_degrees().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, F, D>(deserializer: D) -> Result<Angle<F>, D::Error>
+        where F: FullFloat + Deserialize<'de>, D: Deserializer<'de>
+    {
+        let degrees = F::deserialize(deserializer)?;
+        check_finite(&[degrees]).map_err(D::Error::custom)?;
+        Ok(Angle::from_degrees(degrees))
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Rotations
+
+/// Serializes an `NQuat` as Euler angles `[x, y, z]` in degrees.
+///
+/// The rotation is about the X axis first, then Y, then Z (all about the
+/// fixed axes), i.e. roll, pitch then yaw for a Z-up frame.
+pub mod euler {
+    use super::*;
+
+    pub fn serialize<F, S>(value: &NQuat<F>, serializer: S) -> Result<S::Ok, S::Error>
+        where F: FullFloat + Serialize, S: Serializer
+    {
+        let (x, y, z) = to_euler(value);
+        [x.as_degrees(), y.as_degrees(), z.as_degrees()].serialize(serializer)
+    }
+
+    pub fn deserialize<'de, F, D>(deserializer: D) -> Result<NQuat<F>, D::Error>
+        where F: FullFloat + Deserialize<'de>, D: Deserializer<'de>
+    {
+        let a = <[F; 3]>::deserialize(deserializer)?;
+        check_finite(&a).map_err(D::Error::custom)?;
+        Ok(from_euler(Angle::from_degrees(a[0]),
+                      Angle::from_degrees(a[1]),
+                      Angle::from_degrees(a[2])))
+    }
+}
+
+fn from_euler<F: FullFloat>(x: Angle<F>, y: Angle<F>, z: Angle<F>) -> NQuat<F> {
+    let two: F = NumCast::from(2.0_f32).unwrap();
+    let (sx, cx) = (x.as_radians() / two).sin_cos();
+    let (sy, cy) = (y.as_radians() / two).sin_cos();
+    let (sz, cz) = (z.as_radians() / two).sin_cos();
+    // qz * qy * qx
+    From::from(Quat::new(
+        Vec3::new(sx * cy * cz - cx * sy * sz,
+                  cx * sy * cz + sx * cy * sz,
+                  cx * cy * sz - sx * sy * cz),
+        cx * cy * cz + sx * sy * sz))
+}
+
+fn to_euler<F: FullFloat>(q: &NQuat<F>) -> (Angle<F>, Angle<F>, Angle<F>) {
+    let one = F::one();
+    let two: F = NumCast::from(2.0_f32).unwrap();
+    let q: Quat<F> = From::from(*q);
+    let (x, y, z, w) = (q.v.x, q.v.y, q.v.z, q.w);
+    let ax = (two * (w * x + y * z)).atan2(one - two * (x * x + y * y));
+    let ay = (two * (w * y - z * x)).max(-one).min(one).asin();
+    let az = (two * (w * z + x * y)).atan2(one - two * (y * y + z * z));
+    (Angle::from_radians(ax), Angle::from_radians(ay), Angle::from_radians(az))
+}
+
+#[derive(Serialize, Deserialize)]
+struct AxisAngle<F> {
+    axis: [F; 3],
+    degrees: F,
+}
+
+/// Serializes an `NQuat` as `{axis: [x, y, z], degrees: a}`.
+///
+/// The identity rotation is written about the X axis.  On deserialization the
+/// axis need not be normalized, but must not be zero.
+pub mod axis_angle {
+    use super::*;
+
+    pub fn serialize<F, S>(value: &NQuat<F>, serializer: S) -> Result<S::Ok, S::Error>
+        where F: FullFloat + Serialize, S: Serializer
+    {
+        let two: F = NumCast::from(2.0_f32).unwrap();
+        let q: Quat<F> = From::from(*value);
+        let s = q.v.magnitude();
+        let axis = if s > F::zero() {
+            q.v / s
+        } else {
+            Vec3::new(F::one(), F::zero(), F::zero())
+        };
+        let angle = Angle::from_radians(two * s.atan2(q.w));
+        AxisAngle {
+            axis: [axis.x, axis.y, axis.z],
+            degrees: angle.as_degrees(),
+        }.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, F, D>(deserializer: D) -> Result<NQuat<F>, D::Error>
+        where F: FullFloat + Deserialize<'de>, D: Deserializer<'de>
+    {
+        let aa = AxisAngle::<F>::deserialize(deserializer)?;
+        check_finite(&[aa.axis[0], aa.axis[1], aa.axis[2], aa.degrees])
+            .map_err(D::Error::custom)?;
+        let axis = Direction3::try_from_vec(Vec3::new(aa.axis[0], aa.axis[1], aa.axis[2]))
+            .map_err(D::Error::custom)?;
+        Ok(NQuat::from_axis_angle(&axis, &Angle::from_degrees(aa.degrees)))
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use serde::{Serialize, Deserialize};
+    use serde::de::DeserializeOwned;
+    use float_cmp::ApproxEq;
+    use crate::{Vec2, Vec3, Vec4, Point3, Direction3, Mat3, Mat4, Quat, NQuat, Angle};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Arrays {
+        #[serde(with = "crate::serde_repr::array")]
+        v2: Vec2<f32>,
+        #[serde(with = "crate::serde_repr::array")]
+        v4: Vec4<f32>,
+        #[serde(with = "crate::serde_repr::array")]
+        point: Point3<f32>,
+        #[serde(with = "crate::serde_repr::array")]
+        dir: Direction3<f32>,
+        #[serde(with = "crate::serde_repr::array")]
+        quat: Quat<f32>,
+        #[serde(with = "crate::serde_repr::array")]
+        m3: Mat3<f32>,
+        #[serde(with = "crate::serde_repr::array")]
+        m4: Mat4<f64>,
+    }
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Named {
+        #[serde(with = "crate::serde_repr::named")]
+        point: Point3<f32>,
+        #[serde(with = "crate::serde_repr::named")]
+        ori: NQuat<f32>,
+    }
+
+    #[derive(Debug, Serialize, Deserialize)]
+    struct Rotations {
+        #[serde(with = "crate::serde_repr::degrees")]
+        angle: Angle<f64>,
+        #[serde(with = "crate::serde_repr::euler")]
+        euler: NQuat<f64>,
+        #[serde(with = "crate::serde_repr::axis_angle")]
+        axis_angle: NQuat<f64>,
+    }
+
+    fn round_trip<T: Serialize + DeserializeOwned>(value: &T) -> (T, T) {
+        let json = serde_json::to_string(value).unwrap();
+        let bin = bincode::serialize(value).unwrap();
+        (serde_json::from_str(&json).unwrap(), bincode::deserialize(&bin).unwrap())
+    }
+
+    fn arrays() -> Arrays {
+        Arrays {
+            v2: Vec2::new(1.0, 2.0),
+            v4: Vec4::new(1.0, 2.0, 3.0, 4.0),
+            point: Point3::new(1.0, 2.0, 3.0),
+            dir: Direction3::new_isnormal(0.0, 0.0, 1.0),
+            quat: Quat::new(Vec3::new(1.0, 2.0, 3.0), 4.0),
+            m3: Mat3::new(1.0, 2.0, 3.0,
+                          4.0, 5.0, 6.0,
+                          7.0, 8.0, 9.0),
+            m4: Mat4::new(1.0, 2.0, 3.0, 4.0,
+                          5.0, 6.0, 7.0, 8.0,
+                          9.0, 10.0, 11.0, 12.0,
+                          13.0, 14.0, 15.0, 16.0),
+        }
+    }
+
+    #[test]
+    fn test_array_format() {
+        let json = serde_json::to_value(arrays()).unwrap();
+        assert_eq!(json["v2"], serde_json::json!([1.0, 2.0]));
+        assert_eq!(json["point"], serde_json::json!([1.0, 2.0, 3.0]));
+        assert_eq!(json["quat"], serde_json::json!([1.0, 2.0, 3.0, 4.0]));
+        assert_eq!(json["m3"], serde_json::json!([1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0]));
+    }
+
+    #[test]
+    fn test_array_round_trip() {
+        let a = arrays();
+        let (from_json, from_bin) = round_trip(&a);
+        assert_eq!(from_json, a);
+        assert_eq!(from_bin, a);
+    }
+
+    #[test]
+    fn test_array_validation() {
+        let json = serde_json::to_string(&arrays()).unwrap();
+
+        // Directions are renormalized
+        let scaled = json.replace("\"dir\":[0.0,0.0,1.0]", "\"dir\":[0.0,0.0,5.0]");
+        assert!(scaled != json);
+        let a: Arrays = serde_json::from_str(&scaled).unwrap();
+        assert_eq!(a.dir, Direction3::new_isnormal(0.0, 0.0, 1.0));
+
+        // Zero directions are rejected, but not huge or tiny ones
+        let zero = json.replace("\"dir\":[0.0,0.0,1.0]", "\"dir\":[0.0,0.0,0.0]");
+        assert!(serde_json::from_str::<Arrays>(&zero).is_err());
+        for extreme in &["1e20", "1e-30"] {
+            let dir = format!("\"dir\":[0.0,{},0.0]", extreme);
+            let a: Arrays = serde_json::from_str(&json.replace("\"dir\":[0.0,0.0,1.0]", &dir))
+                .unwrap();
+            assert_eq!(a.dir, Direction3::new_isnormal(0.0, 1.0, 0.0));
+        }
+
+        #[derive(Debug, Deserialize)]
+        struct Unit {
+            #[serde(with = "crate::serde_repr::array")]
+            q: NQuat<f32>,
+        }
+        let u: Unit = serde_json::from_str("{\"q\":[1e20,0,0,0]}").unwrap();
+        assert_eq!(u.q, NQuat::new_isnormal(Vec3::new(1.0, 0.0, 0.0), 0.0));
+        assert!(serde_json::from_str::<Unit>("{\"q\":[0,0,0,0]}").is_err());
+
+        // Wrong lengths are rejected
+        let short = json.replace("\"v2\":[1.0,2.0]", "\"v2\":[1.0]");
+        assert!(serde_json::from_str::<Arrays>(&short).is_err());
+
+        // Non-finite values are rejected (JSON cannot express them, bincode can)
+        let mut a = arrays();
+        a.v4.w = f32::NAN;
+        let bin = bincode::serialize(&a).unwrap();
+        assert!(bincode::deserialize::<Arrays>(&bin).is_err());
+    }
+
+    #[test]
+    fn test_named() {
+        let axis: Direction3<f32> = From::from(Vec3::new(1.0, 1.0, 0.0));
+        let n = Named {
+            point: Point3::new(1.0, 2.0, 3.0),
+            ori: NQuat::from_axis_angle(&axis, &Angle::from_degrees(30.0)),
+        };
+        let json = serde_json::to_value(&n).unwrap();
+        assert_eq!(json["point"], serde_json::json!({"x": 1.0, "y": 2.0, "z": 3.0}));
+        assert!(json["ori"]["w"].is_number());
+
+        let (from_json, from_bin) = round_trip(&n);
+        assert_eq!(from_json, n);
+        assert_eq!(from_bin, n);
+
+        let q: Named = serde_json::from_str(
+            r#"{"point": {"x": 0, "y": 0, "z": 0}, "ori": {"x": 0, "y": 0, "z": 0, "w": 2}}"#
+        ).unwrap();
+        assert_eq!(q.ori, NQuat::identity());
+    }
+
+    #[test]
+    fn test_rotations() {
+        let margin = (1e-12, 4);
+        let axis: Direction3<f64> = From::from(Vec3::new(0.3, -1.0, 0.5));
+        let q = NQuat::from_axis_angle(&axis, &Angle::from_degrees(72.0));
+        let r = Rotations {
+            angle: Angle::from_degrees(90.0),
+            euler: q,
+            axis_angle: q,
+        };
+
+        let json = serde_json::to_value(&r).unwrap();
+        assert!((json["angle"].as_f64().unwrap() - 90.0).abs() < 1e-12);
+        assert_eq!(json["euler"].as_array().unwrap().len(), 3);
+
+        let (from_json, from_bin) = round_trip(&r);
+        for back in [from_json, from_bin].iter() {
+            assert!(back.angle.approx_eq(&r.angle, margin));
+            // q and -q are the same rotation
+            let e: Quat<f64> = From::from(back.euler);
+            let a: Quat<f64> = From::from(back.axis_angle);
+            let q: Quat<f64> = From::from(q);
+            assert!(e.approx_eq(&q, margin) || e.approx_eq(&(q * -1.0), margin));
+            assert!(a.approx_eq(&q, margin) || a.approx_eq(&(q * -1.0), margin));
+        }
+    }
+
+    #[test]
+    fn test_euler_format() {
+        let r: Rotations = serde_json::from_str(
+            r#"{"angle": 45, "euler": [0, 0, 90], "axis_angle": {"axis": [0, 0, 2], "degrees": 90}}"#
+        ).unwrap();
+        let margin = (1e-12, 4);
+        assert!(r.angle.approx_eq(&Angle::from_degrees(45.0), margin));
+        // both represent a quarter turn about Z
+        let v = r.euler.rotate(Vec3::new(1.0, 0.0, 0.0));
+        assert!(v.approx_eq(&Vec3::new(0.0, 1.0, 0.0), margin));
+        let v = r.axis_angle.rotate(Vec3::new(1.0, 0.0, 0.0));
+        assert!(v.approx_eq(&Vec3::new(0.0, 1.0, 0.0), margin));
+
+        // identity is written about the X axis, with no NaNs
+        let id = Rotations {
+            angle: Angle::from_degrees(0.0),
+            euler: NQuat::identity(),
+            axis_angle: NQuat::identity(),
+        };
+        let json = serde_json::to_string(&id).unwrap();
+        assert!(json.contains(r#""axis_angle":{"axis":[1.0,0.0,0.0],"degrees":0.0}"#));
+
+        assert!(serde_json::from_str::<Rotations>(
+            r#"{"angle": 45, "euler": [0, 0, 90], "axis_angle": {"axis": [0, 0, 0], "degrees": 90}}"#
+        ).is_err());
+    }
+}
@@ -0,0 +1,395 @@
+
+//! Points, vectors and transforms tagged with the coordinate space they are
+//! in, so that mixing spaces is a compile error.
+//!
+//! Spaces are marker types such as `World` and `Local`; define your own the
+//! same way (any type will do).  A `Point3In<F, World>` can only be offset by
+//! a `Vec3In<F, World>`, and a `Mat4From<F, Local, World>` only applied to
+//! things in `Local`, giving things in `World`.  Transforms compose when the
+//! spaces line up: `Mat4From<F, B, C> * Mat4From<F, A, B>` is a
+//! `Mat4From<F, A, C>`.
+//!
+//! The tags are zero-sized, so each type has the same layout as the untagged
+//! type it wraps.  Use `tag` to enter a space and `untag` (or `From`) to
+//! leave it.
+//!
+//! ```compile_fail
+//! use siege_math::{Point3In, Vec3In};
+//! use siege_math::space::{World, Local};
+//!
+//! let p: Point3In<f32, World> = Point3In::new(0.0, 0.0, 0.0);
+//! let offset: Vec3In<f32, Local> = Vec3In::new(1.0, 0.0, 0.0);
+//! let q = p + offset; // error: offset is in the wrong space
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+use std::ops::{Add, Sub, Mul, Neg};
+use crate::{FullFloat, Vec3, Point3, Mat3, Mat4, NQuat, Position};
+
+/// World space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum World {}
+
+/// An object's local space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Local {}
+
+/// View (camera) space
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum View {}
+
+// The derives would require the space markers to be Clone, Debug, etc.
+// themselves, so these are written out.
+macro_rules! impl_tagged {
+    ($T:ident < $($S:ident),* >, $name:expr) => {
+        impl<F: Copy, $($S),*> Clone for $T<F, $($S),*> {
+            fn clone(&self) -> Self { *self }
+        }
+
+        impl<F: Copy, $($S),*> Copy for $T<F, $($S),*> { }
+
+        impl<F: PartialEq, $($S),*> PartialEq for $T<F, $($S),*> {
+            fn eq(&self, other: &Self) -> bool { self.inner == other.inner }
+        }
+
+        impl<F: fmt::Debug, $($S),*> fmt::Debug for $T<F, $($S),*> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.debug_tuple($name).field(&self.inner).finish()
+            }
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Points and vectors
+
+/// A point in space `S`
+#[repr(transparent)]
+pub struct Point3In<F, S> {
+    inner: Point3<F>,
+    space: PhantomData<S>,
+}
+
+/// A vector in space `S`
+#[repr(transparent)]
+pub struct Vec3In<F, S> {
+    inner: Vec3<F>,
+    space: PhantomData<S>,
+}
+
+impl_tagged!(Point3In<S>, "Point3In");
+impl_tagged!(Vec3In<S>, "Vec3In");
+
+impl<F: FullFloat, S> Point3In<F, S> {
+    pub fn new(x: F, y: F, z: F) -> Point3In<F, S> {
+        Point3In::tag(Point3::new(x, y, z))
+    }
+
+    /// Declare that `p` is in space `S`
+    pub fn tag(p: Point3<F>) -> Point3In<F, S> {
+        Point3In { inner: p, space: PhantomData }
+    }
+
+    pub fn untag(&self) -> Point3<F> {
+        self.inner
+    }
+}
+
+impl<F: FullFloat, S> Vec3In<F, S> {
+    pub fn new(x: F, y: F, z: F) -> Vec3In<F, S> {
+        Vec3In::tag(Vec3::new(x, y, z))
+    }
+
+    /// Declare that `v` is in space `S`
+    pub fn tag(v: Vec3<F>) -> Vec3In<F, S> {
+        Vec3In { inner: v, space: PhantomData }
+    }
+
+    pub fn untag(&self) -> Vec3<F> {
+        self.inner
+    }
+
+    pub fn magnitude(&self) -> F {
+        self.inner.magnitude()
+    }
+
+    pub fn dot(&self, other: Vec3In<F, S>) -> F {
+        self.inner.dot(other.inner)
+    }
+
+    pub fn cross(&self, other: Vec3In<F, S>) -> Vec3In<F, S> {
+        Vec3In::tag(self.inner.cross(other.inner))
+    }
+}
+
+impl<F: FullFloat, S> From<Point3In<F, S>> for Point3<F> {
+    fn from(p: Point3In<F, S>) -> Point3<F> {
+        p.inner
+    }
+}
+
+impl<F: FullFloat, S> From<Vec3In<F, S>> for Vec3<F> {
+    fn from(v: Vec3In<F, S>) -> Vec3<F> {
+        v.inner
+    }
+}
+
+impl<F: FullFloat, S> Sub for Point3In<F, S> {
+    type Output = Vec3In<F, S>;
+
+    fn sub(self, rhs: Point3In<F, S>) -> Vec3In<F, S> {
+        Vec3In::tag(self.inner - rhs.inner)
+    }
+}
+
+impl<F: FullFloat, S> Add<Vec3In<F, S>> for Point3In<F, S> {
+    type Output = Point3In<F, S>;
+
+    fn add(self, rhs: Vec3In<F, S>) -> Point3In<F, S> {
+        Point3In::tag(self.inner + rhs.inner)
+    }
+}
+
+impl<F: FullFloat, S> Sub<Vec3In<F, S>> for Point3In<F, S> {
+    type Output = Point3In<F, S>;
+
+    fn sub(self, rhs: Vec3In<F, S>) -> Point3In<F, S> {
+        Point3In::tag(self.inner - rhs.inner)
+    }
+}
+
+impl<F: FullFloat, S> Add for Vec3In<F, S> {
+    type Output = Vec3In<F, S>;
+
+    fn add(self, rhs: Vec3In<F, S>) -> Vec3In<F, S> {
+        Vec3In::tag(self.inner + rhs.inner)
+    }
+}
+
+impl<F: FullFloat, S> Sub for Vec3In<F, S> {
+    type Output = Vec3In<F, S>;
+
+    fn sub(self, rhs: Vec3In<F, S>) -> Vec3In<F, S> {
+        Vec3In::tag(self.inner - rhs.inner)
+    }
+}
+
+impl<F: FullFloat, S> Mul<F> for Vec3In<F, S> {
+    type Output = Vec3In<F, S>;
+
+    fn mul(self, rhs: F) -> Vec3In<F, S> {
+        Vec3In::tag(self.inner * rhs)
+    }
+}
+
+impl<F: FullFloat, S> Neg for Vec3In<F, S> {
+    type Output = Vec3In<F, S>;
+
+    fn neg(self) -> Vec3In<F, S> {
+        Vec3In::tag(-self.inner)
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Transforms
+
+/// An affine transform taking space `A` to space `B`
+#[repr(transparent)]
+pub struct Mat4From<F, A, B> {
+    inner: Mat4<F>,
+    space: PhantomData<(A, B)>,
+}
+
+/// The position (and orientation) of space `A` within space `B`, which
+/// takes points in `A` to `B`
+#[repr(transparent)]
+pub struct PositionFrom<F, A, B> {
+    inner: Position<F>,
+    space: PhantomData<(A, B)>,
+}
+
+impl_tagged!(Mat4From<A, B>, "Mat4From");
+impl_tagged!(PositionFrom<A, B>, "PositionFrom");
+
+impl<F: FullFloat, A, B> Mat4From<F, A, B> {
+    /// Declare that `m` takes space `A` to space `B`
+    pub fn tag(m: Mat4<F>) -> Mat4From<F, A, B> {
+        Mat4From { inner: m, space: PhantomData }
+    }
+
+    pub fn untag(&self) -> Mat4<F> {
+        self.inner
+    }
+
+    /// The transform back from `B` to `A`, or None if singular
+    pub fn inverse(&self) -> Option<Mat4From<F, B, A>> {
+        self.inner.inverse().map(Mat4From::tag)
+    }
+}
+
+impl<F: FullFloat, A, B> PositionFrom<F, A, B> {
+    /// Declare that `p` is the position of space `A` within space `B`
+    pub fn tag(p: Position<F>) -> PositionFrom<F, A, B> {
+        PositionFrom { inner: p, space: PhantomData }
+    }
+
+    pub fn untag(&self) -> Position<F> {
+        self.inner
+    }
+
+    /// The position of `B` within `A`
+    pub fn inverse(&self) -> PositionFrom<F, B, A> {
+        let ori = self.inner.ori.conjugate();
+        PositionFrom::tag(Position {
+            point: Point3(-ori.rotate(self.inner.point.0)),
+            ori,
+        })
+    }
+
+    pub fn to_mat4(&self) -> Mat4From<F, A, B> {
+        let r: Mat3<F> = From::from(self.inner.ori);
+        Mat4From::tag(Mat4::from_mat3(r, self.inner.point))
+    }
+
+    pub fn transform_point(&self, p: &Point3In<F, A>) -> Point3In<F, B> {
+        Point3In::tag(Point3(self.inner.ori.rotate(p.inner.0) + self.inner.point.0))
+    }
+
+    pub fn transform_vector(&self, v: &Vec3In<F, A>) -> Vec3In<F, B> {
+        Vec3In::tag(self.inner.ori.rotate(v.inner))
+    }
+}
+
+impl<F: FullFloat, A, B> Mat4From<F, A, B> {
+    pub fn transform_point(&self, p: &Point3In<F, A>) -> Point3In<F, B> {
+        Point3In::tag(self.inner.transform_point(&p.inner))
+    }
+
+    pub fn transform_vector(&self, v: &Vec3In<F, A>) -> Vec3In<F, B> {
+        Vec3In::tag(self.inner.transform_vector(&v.inner))
+    }
+}
+
+impl<F: FullFloat, A, B> From<Mat4From<F, A, B>> for Mat4<F> {
+    fn from(m: Mat4From<F, A, B>) -> Mat4<F> {
+        m.inner
+    }
+}
+
+impl<F: FullFloat, A, B> From<PositionFrom<F, A, B>> for Position<F> {
+    fn from(p: PositionFrom<F, A, B>) -> Position<F> {
+        p.inner
+    }
+}
+
+/// `b_to_c * a_to_b` applies `a_to_b` first
+impl<F: FullFloat, A, B, C> Mul<Mat4From<F, A, B>> for Mat4From<F, B, C> {
+    type Output = Mat4From<F, A, C>;
+
+    fn mul(self, rhs: Mat4From<F, A, B>) -> Mat4From<F, A, C> {
+        Mat4From::tag(self.inner * rhs.inner)
+    }
+}
+
+/// `b_in_c * a_in_b` is `a_in_c`
+impl<F: FullFloat, A, B, C> Mul<PositionFrom<F, A, B>> for PositionFrom<F, B, C> {
+    type Output = PositionFrom<F, A, C>;
+
+    fn mul(self, rhs: PositionFrom<F, A, B>) -> PositionFrom<F, A, C> {
+        let ori: NQuat<F> = self.inner.ori * rhs.inner.ori;
+        let point = Point3(self.inner.ori.rotate(rhs.inner.point.0) + self.inner.point.0);
+        PositionFrom::tag(Position { point, ori })
+    }
+}
+
+impl<F: FullFloat, A, B> Mul<Point3In<F, A>> for Mat4From<F, A, B> {
+    type Output = Point3In<F, B>;
+
+    fn mul(self, rhs: Point3In<F, A>) -> Point3In<F, B> {
+        self.transform_point(&rhs)
+    }
+}
+
+impl<F: FullFloat, A, B> Mul<Vec3In<F, A>> for Mat4From<F, A, B> {
+    type Output = Vec3In<F, B>;
+
+    fn mul(self, rhs: Vec3In<F, A>) -> Vec3In<F, B> {
+        self.transform_vector(&rhs)
+    }
+}
+
+impl<F: FullFloat, A, B> Mul<Point3In<F, A>> for PositionFrom<F, A, B> {
+    type Output = Point3In<F, B>;
+
+    fn mul(self, rhs: Point3In<F, A>) -> Point3In<F, B> {
+        self.transform_point(&rhs)
+    }
+}
+
+impl<F: FullFloat, A, B> Mul<Vec3In<F, A>> for PositionFrom<F, A, B> {
+    type Output = Vec3In<F, B>;
+
+    fn mul(self, rhs: Vec3In<F, A>) -> Vec3In<F, B> {
+        self.transform_vector(&rhs)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use std::mem::size_of;
+    use float_cmp::ApproxEq;
+    use crate::{Vec3, Point3, Direction3, Mat4, NQuat, Angle, Position};
+    use super::{World, Local, View, Point3In, Vec3In, Mat4From, PositionFrom};
+
+    const MARGIN: (f64, i64) = (1e-12, 8);
+
+    #[test]
+    fn test_zero_cost() {
+        assert_eq!(size_of::<Point3In<f32, World>>(), size_of::<Point3<f32>>());
+        assert_eq!(size_of::<Vec3In<f64, Local>>(), size_of::<Vec3<f64>>());
+        assert_eq!(size_of::<Mat4From<f32, Local, World>>(), size_of::<Mat4<f32>>());
+        assert_eq!(size_of::<PositionFrom<f64, Local, World>>(), size_of::<Position<f64>>());
+    }
+
+    #[test]
+    fn test_points() {
+        let a: Point3In<f64, World> = Point3In::new(1.0, 2.0, 3.0);
+        let b: Point3In<f64, World> = Point3In::new(4.0, 6.0, 3.0);
+        let d = b - a;
+        assert_eq!(d.magnitude(), 5.0);
+        assert_eq!(a + d, b);
+        assert_eq!((b - d).untag(), Point3::new(1.0, 2.0, 3.0));
+        let v: Vec3<f64> = From::from(-d * 2.0);
+        assert_eq!(v, Vec3::new(-6.0, -8.0, 0.0));
+    }
+
+    #[test]
+    fn test_transforms() {
+        let axis: Direction3<f64> = From::from(Vec3::new(0.0, 1.0, 1.0));
+        let body: PositionFrom<f64, Local, World> = PositionFrom::tag(Position {
+            point: Point3::new(10.0, 0.0, -5.0),
+            ori: NQuat::from_axis_angle(&axis, &Angle::from_degrees(60.0)),
+        });
+        let camera: PositionFrom<f64, View, World> = PositionFrom::tag(Position {
+            point: Point3::new(0.0, 2.0, 8.0),
+            ori: NQuat::from_axis_angle(&axis, &Angle::from_degrees(-20.0)),
+        });
+
+        let p: Point3In<f64, Local> = Point3In::new(1.0, -1.0, 0.5);
+        let world = body * p;
+        let local_to_view: PositionFrom<f64, Local, View> = camera.inverse() * body;
+        let in_view = local_to_view * p;
+        assert!((camera * in_view).untag().approx_eq(&world.untag(), MARGIN));
+
+        // the same through matrices
+        let m: Mat4From<f64, Local, View> = camera.to_mat4().inverse().unwrap() * body.to_mat4();
+        assert!((m * p).untag().approx_eq(&in_view.untag(), MARGIN));
+        let v: Vec3In<f64, Local> = Vec3In::new(0.0, 0.0, 1.0);
+        assert!((m * v).untag().approx_eq(&(local_to_view * v).untag(), MARGIN));
+        let back = m.inverse().unwrap() * in_view;
+        assert!(back.untag().approx_eq(&p.untag(), MARGIN));
+    }
+}
@@ -0,0 +1,273 @@
+
+use std::ops::Mul;
+use serde::{Serialize, Deserialize};
+use float_cmp::ApproxEq;
+use crate::{FullFloat, Vec2, Vec3, Point2, Direction2, Mat2, Mat3, Rot2};
+
+/// A rigid transform in 2 dimensions: a rotation followed by a translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Transform2<F> {
+    pub rotation: Rot2<F>,
+    pub translation: Vec2<F>,
+}
+
+impl<F: FullFloat> Transform2<F> {
+    pub fn new(rotation: Rot2<F>, translation: Vec2<F>) -> Transform2<F> {
+        Transform2 { rotation, translation }
+    }
+}
+
+impl<F: FullFloat> Transform2<F> {
+    pub fn identity() -> Transform2<F> {
+        Transform2::new(Rot2::identity(), Vec2::new(F::zero(), F::zero()))
+    }
+}
+
+impl<F: FullFloat> Default for Transform2<F> {
+    fn default() -> Transform2<F> {
+        Transform2::identity()
+    }
+}
+
+impl<F: FullFloat> Transform2<F> {
+    pub fn inverse(&self) -> Transform2<F> {
+        let r = self.rotation.inverse();
+        Transform2::new(r, -r.rotate(self.translation))
+    }
+}
+
+impl<F: FullFloat> Transform2<F> {
+    pub fn transform_point(&self, p: Point2<F>) -> Point2<F> {
+        Point2(self.rotation.rotate(p.0) + self.translation)
+    }
+
+    /// Vectors are rotated but not translated
+    pub fn transform_vector(&self, v: Vec2<F>) -> Vec2<F> {
+        self.rotation.rotate(v)
+    }
+
+    pub fn transform_direction(&self, d: Direction2<F>) -> Direction2<F> {
+        self.rotation * d
+    }
+}
+
+/// `a * b` applies `b` first, then `a`
+impl<F: FullFloat> Mul for Transform2<F> {
+    type Output = Transform2<F>;
+
+    fn mul(self, rhs: Transform2<F>) -> Transform2<F> {
+        Transform2::new(self.rotation * rhs.rotation,
+                        self.rotation.rotate(rhs.translation) + self.translation)
+    }
+}
+
+impl<F: FullFloat> Mul<Point2<F>> for Transform2<F> {
+    type Output = Point2<F>;
+
+    fn mul(self, rhs: Point2<F>) -> Point2<F> {
+        self.transform_point(rhs)
+    }
+}
+
+impl<F: FullFloat> From<Transform2<F>> for Mat3<F> {
+    fn from(t: Transform2<F>) -> Mat3<F> {
+        let a: Affine2<F> = From::from(t);
+        From::from(a)
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Transform2<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.rotation.approx_eq(&other.rotation, margin)
+            && self.translation.approx_eq(&other.translation, margin)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+/// A general affine transform in 2 dimensions: a linear part (rotation,
+/// scale, shear) followed by a translation
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct Affine2<F> {
+    pub linear: Mat2<F>,
+    pub translation: Vec2<F>,
+}
+
+impl<F: FullFloat> Affine2<F> {
+    pub fn new(linear: Mat2<F>, translation: Vec2<F>) -> Affine2<F> {
+        Affine2 { linear, translation }
+    }
+}
+
+impl<F: FullFloat> Affine2<F> {
+    pub fn identity() -> Affine2<F> {
+        Affine2::new(Mat2::identity(), Vec2::new(F::zero(), F::zero()))
+    }
+}
+
+impl<F: FullFloat> Default for Affine2<F> {
+    fn default() -> Affine2<F> {
+        Affine2::identity()
+    }
+}
+
+impl<F: FullFloat> Affine2<F> {
+    pub fn from_translation(t: Vec2<F>) -> Affine2<F> {
+        Affine2::new(Mat2::identity(), t)
+    }
+
+    pub fn from_rotation(r: Rot2<F>) -> Affine2<F> {
+        Affine2::new(From::from(r), Vec2::new(F::zero(), F::zero()))
+    }
+
+    /// Non-uniform scale along the X and Y axes
+    pub fn from_scale(s: Vec2<F>) -> Affine2<F> {
+        Affine2::new(Mat2::new(s.x, F::zero(),
+                               F::zero(), s.y),
+                     Vec2::new(F::zero(), F::zero()))
+    }
+
+    /// Shear: x' = x + `x_by_y` * y, and y' = y + `y_by_x` * x
+    pub fn from_shear(x_by_y: F, y_by_x: F) -> Affine2<F> {
+        Affine2::new(Mat2::new(F::one(), x_by_y,
+                               y_by_x, F::one()),
+                     Vec2::new(F::zero(), F::zero()))
+    }
+
+    /// Scale, then rotate, then translate
+    pub fn from_scale_rotation_translation(s: Vec2<F>, r: Rot2<F>, t: Vec2<F>) -> Affine2<F> {
+        let r: Mat2<F> = From::from(r);
+        let s = Affine2::from_scale(s).linear;
+        Affine2::new(r * s, t)
+    }
+}
+
+impl<F: FullFloat> Affine2<F> {
+    /// The inverse transform, or None if the linear part is singular
+    pub fn inverse(&self) -> Option<Affine2<F>> {
+        let inv = self.linear.inverse()?;
+        let t = inv * self.translation;
+        Some(Affine2::new(inv, -t))
+    }
+}
+
+impl<F: FullFloat> Affine2<F> {
+    pub fn transform_point(&self, p: Point2<F>) -> Point2<F> {
+        Point2(self.linear * p.0 + self.translation)
+    }
+
+    /// Vectors are transformed by the linear part only
+    pub fn transform_vector(&self, v: Vec2<F>) -> Vec2<F> {
+        self.linear * v
+    }
+}
+
+/// `a * b` applies `b` first, then `a`
+impl<F: FullFloat> Mul for Affine2<F> {
+    type Output = Affine2<F>;
+
+    fn mul(self, rhs: Affine2<F>) -> Affine2<F> {
+        Affine2::new(self.linear * rhs.linear,
+                     self.linear * rhs.translation + self.translation)
+    }
+}
+
+impl<F: FullFloat> Mul<Point2<F>> for Affine2<F> {
+    type Output = Point2<F>;
+
+    fn mul(self, rhs: Point2<F>) -> Point2<F> {
+        self.transform_point(rhs)
+    }
+}
+
+impl<F: FullFloat> From<Transform2<F>> for Affine2<F> {
+    fn from(t: Transform2<F>) -> Affine2<F> {
+        Affine2::new(From::from(t.rotation), t.translation)
+    }
+}
+
+/// The homogeneous matrix, acting on (x, y, 1) column vectors
+impl<F: FullFloat> From<Affine2<F>> for Mat3<F> {
+    fn from(a: Affine2<F>) -> Mat3<F> {
+        Mat3::from_cols(Vec3::new(a.linear.x.x, a.linear.x.y, F::zero()),
+                        Vec3::new(a.linear.y.x, a.linear.y.y, F::zero()),
+                        Vec3::new(a.translation.x, a.translation.y, F::one()))
+    }
+}
+
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Affine2<F> {
+    type Margin = M;
+
+    fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
+        let margin = margin.into();
+        self.linear.approx_eq(&other.linear, margin)
+            && self.translation.approx_eq(&other.translation, margin)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Vec2, Vec3, Point2, Mat3, Rot2, Angle};
+    use super::{Transform2, Affine2};
+
+    const MARGIN: (f64, i64) = (1e-12, 8);
+
+    fn homogeneous(m: &Mat3<f64>, p: Point2<f64>) -> Point2<f64> {
+        let v = m * Vec3::new(p.x, p.y, 1.0);
+        Point2::new(v.x, v.y)
+    }
+
+    #[test]
+    fn test_transform() {
+        let t = Transform2::new(Rot2::from_angle(Angle::from_degrees(90.0)),
+                                Vec2::new(10.0, 0.0));
+        let p = Point2::new(1.0, 0.0);
+        assert!(t.transform_point(p).approx_eq(&Point2::new(10.0, 1.0), MARGIN));
+        assert!(t.transform_vector(Vec2::new(1.0, 0.0)).approx_eq(&Vec2::new(0.0, 1.0), MARGIN));
+        assert!(t.inverse().transform_point(t * p).approx_eq(&p, MARGIN));
+        assert!((t * t.inverse()).approx_eq(&Transform2::identity(), MARGIN));
+
+        let u = Transform2::new(Rot2::from_angle(Angle::from_degrees(-30.0)),
+                                Vec2::new(-2.0, 5.0));
+        assert!(((t * u) * p).approx_eq(&(t * (u * p)), MARGIN));
+
+        let m: Mat3<f64> = From::from(t * u);
+        assert!(homogeneous(&m, p).approx_eq(&(t * (u * p)), MARGIN));
+    }
+
+    #[test]
+    fn test_affine() {
+        let a = Affine2::from_scale_rotation_translation(
+            Vec2::new(2.0, 0.5), Rot2::from_angle(Angle::from_degrees(90.0)), Vec2::new(1.0, 1.0));
+        let p = Point2::new(1.0, 2.0);
+        // scale to (2, 1), rotate to (-1, 2), translate to (0, 3)
+        assert!(a.transform_point(p).approx_eq(&Point2::new(0.0, 3.0), MARGIN));
+
+        let shear = Affine2::from_shear(0.5, 0.0);
+        assert!(shear.transform_point(p).approx_eq(&Point2::new(2.0, 2.0), MARGIN));
+
+        let c = a * shear * Affine2::from_translation(Vec2::new(-3.0, 0.25));
+        let inv = c.inverse().unwrap();
+        assert!(inv.transform_point(c * p).approx_eq(&p, MARGIN));
+        assert!((c * inv).approx_eq(&Affine2::identity(), MARGIN));
+
+        let m: Mat3<f64> = From::from(c);
+        assert!(homogeneous(&m, p).approx_eq(&(c * p), MARGIN));
+        assert_eq!(m[(2,0)], 0.0);
+        assert_eq!(m[(2,2)], 1.0);
+
+        let t = Transform2::new(Rot2::from_angle(Angle::from_degrees(45.0)), Vec2::new(3.0, 4.0));
+        let ta: Affine2<f64> = From::from(t);
+        assert!(ta.transform_point(p).approx_eq(&t.transform_point(p), MARGIN));
+
+        assert!(Affine2::from_scale(Vec2::new(0.0, 1.0)).inverse().is_none());
+    }
+}
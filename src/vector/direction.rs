@@ -1,9 +1,12 @@
 
 use std::ops::{Deref, Neg};
+use std::fmt;
+use std::str::FromStr;
 use serde::{Serialize, Deserialize};
 use float_cmp::ApproxEq;
 use super::{Vec2, Vec3, Vec4};
-use crate::{Angle, FullFloat};
+use crate::{Angle, FullFloat, MathError};
+use crate::parse::{ParseError, normalize_error};
 
 /// Direction vector in 2-dimensions (normalized)
 #[repr(C)]
@@ -37,6 +40,12 @@ pub const Z_AXIS_F64: Direction3<f64> = Direction3::<f64>(
 );
 
 impl<F: FullFloat> Direction2<F> {
+    /// Create from components that are already normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not of unit length.  Use `try_new` to check
+    /// without panicking.
     #[inline]
     pub fn new_isnormal(x: F, y: F) -> Direction2<F> {
         let d = Direction2(Vec2::new(x,y));
@@ -46,6 +55,12 @@ impl<F: FullFloat> Direction2<F> {
 }
 
 impl<F: FullFloat> Direction3<F> {
+    /// Create from components that are already normalized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the vector is not of unit length.  Use `try_new` to check
+    /// without panicking.
     #[inline]
     pub fn new_isnormal(x: F, y: F, z: F) -> Direction3<F> {
         let d = Direction3(Vec3::new(x,y,z));
@@ -54,6 +69,82 @@ impl<F: FullFloat> Direction3<F> {
     }
 }
 
+impl<F: FullFloat> Direction2<F> {
+    /// Create from components that are already normalized, or fail with
+    /// `NonFinite` or `NotNormalized`
+    pub fn try_new(x: F, y: F) -> Result<Direction2<F>, MathError> {
+        if !(x.is_finite() && y.is_finite()) {
+            return Err(MathError::NonFinite);
+        }
+        let v = Vec2::new(x,y);
+        if !v.is_normal() {
+            return Err(MathError::NotNormalized);
+        }
+        Ok(Direction2(v))
+    }
+}
+
+impl<F: FullFloat> Direction3<F> {
+    /// Create from components that are already normalized, or fail with
+    /// `NonFinite` or `NotNormalized`
+    pub fn try_new(x: F, y: F, z: F) -> Result<Direction3<F>, MathError> {
+        if !(x.is_finite() && y.is_finite() && z.is_finite()) {
+            return Err(MathError::NonFinite);
+        }
+        let v = Vec3::new(x,y,z);
+        if !v.is_normal() {
+            return Err(MathError::NotNormalized);
+        }
+        Ok(Direction3(v))
+    }
+}
+
+// `TryFrom<Vec2>` would conflict with the `From<Vec2>` below (through the
+// blanket `TryFrom` for any `Into`), so the fallible normalizing
+// conversions are named functions.
+
+impl<F: FullFloat> Direction2<F> {
+    /// Normalize a vector, or fail with `NonFinite` or `ZeroLength`
+    pub fn try_from_vec(v: Vec2<F>) -> Result<Direction2<F>, MathError> {
+        if !(v.x.is_finite() && v.y.is_finite()) {
+            return Err(MathError::NonFinite);
+        }
+        let mag2 = v.squared_magnitude();
+        if mag2.is_normal() {
+            return Ok(Direction2(v / mag2.sqrt()));
+        }
+        // Otherwise scale by the largest component first, so the magnitude
+        // can neither overflow nor underflow
+        let big = v.x.abs().max(v.y.abs());
+        if big == F::zero() {
+            return Err(MathError::ZeroLength);
+        }
+        let v = v / big;
+        Ok(Direction2(v / v.magnitude()))
+    }
+}
+
+impl<F: FullFloat> Direction3<F> {
+    /// Normalize a vector, or fail with `NonFinite` or `ZeroLength`
+    pub fn try_from_vec(v: Vec3<F>) -> Result<Direction3<F>, MathError> {
+        if !(v.x.is_finite() && v.y.is_finite() && v.z.is_finite()) {
+            return Err(MathError::NonFinite);
+        }
+        let mag2 = v.squared_magnitude();
+        if mag2.is_normal() {
+            return Ok(Direction3(v / mag2.sqrt()));
+        }
+        // Otherwise scale by the largest component first, so the magnitude
+        // can neither overflow nor underflow
+        let big = v.x.abs().max(v.y.abs()).max(v.z.abs());
+        if big == F::zero() {
+            return Err(MathError::ZeroLength);
+        }
+        let v = v / big;
+        Ok(Direction3(v / v.magnitude()))
+    }
+}
+
 impl<F: FullFloat> Deref for Direction2<F> {
     type Target = Vec2<F>;
 
@@ -88,6 +179,8 @@ impl<F: FullFloat> From<Direction3<F>> for Vec4<F> {
     }
 }
 
+/// Normalizes the vector.  A zero vector gives NaN components; use
+/// `Direction2::try_from_vec` when the input may be zero.
 impl<F: FullFloat> From<Vec2<F>> for Direction2<F> {
     fn from(mut v: Vec2<F>) -> Direction2<F> {
         let mag = v.magnitude();
@@ -96,6 +189,8 @@ impl<F: FullFloat> From<Vec2<F>> for Direction2<F> {
         Direction2(v)
     }
 }
+/// Normalizes the vector.  A zero vector gives NaN components; use
+/// `Direction3::try_from_vec` when the input may be zero.
 impl<F: FullFloat> From<Vec3<F>> for Direction3<F> {
     fn from(mut v: Vec3<F>) -> Direction3<F> {
         let mag = v.magnitude();
@@ -107,11 +202,14 @@ impl<F: FullFloat> From<Vec3<F>> for Direction3<F> {
 }
 
 impl<F: FullFloat> Direction3<F> {
-    #[allow(dead_code)]
+    /// Convert from homogeneous coordinates, normalizing.  Returns None
+    /// unless w is zero and the vector part is not.
     #[inline]
-    fn from_vec4(v: Vec4<F>) -> Option<Direction3<F>> {
+    pub fn from_vec4(v: Vec4<F>) -> Option<Direction3<F>> {
         if v.w != F::zero() { return None; }
-        Some(Direction3(v.truncate_w()))
+        let v = v.truncate_w();
+        if v.squared_magnitude() == F::zero() { return None; }
+        Some(From::from(v))
     }
 }
 
@@ -129,6 +227,23 @@ impl<F: FullFloat> Direction3<F> {
     }
 }
 
+impl<F: FullFloat> Direction3<F> {
+    /// Two directions `(b1, b2)` perpendicular to this one and to each
+    /// other, such that `(b1, b2, self)` is a right-handed frame.
+    ///
+    /// This is the branchless construction of Duff et al., "Building an
+    /// Orthonormal Basis, Revisited" (JCGT 2017), which fixes the precision
+    /// problems of Frisvad's method near -Z.
+    pub fn orthonormal_basis(&self) -> (Direction3<F>, Direction3<F>) {
+        let n = self.0;
+        let sign = F::one().copysign(n.z);
+        let a = -F::one() / (sign + n.z);
+        let b = n.x * n.y * a;
+        (Direction3(Vec3::new(F::one() + sign * n.x * n.x * a, sign * b, -sign * n.x)),
+         Direction3(Vec3::new(b, sign + n.y * n.y * a, -n.y)))
+    }
+}
+
 impl<F: FullFloat> Neg for Direction3<F> {
     type Output = Direction3<F>;
 
@@ -138,7 +253,7 @@ impl<F: FullFloat> Neg for Direction3<F> {
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Direction2<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Direction2<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -147,7 +262,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Direc
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Direction3<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Direction3<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -157,6 +272,8 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Direc
 }
 
 impl<F: FullFloat> Direction3<F> {
+    /// Direction on the unit sphere with +Y as north and longitude zero
+    /// along +Z, increasing towards +X
     pub fn from_lat_long(latitude: Angle<F>, longitude: Angle<F>) -> Direction3<F>
     {
         let (slat,clat) = latitude.as_radians().sin_cos();
@@ -164,13 +281,140 @@ impl<F: FullFloat> Direction3<F> {
         Direction3(Vec3 {
             x: clat * slon,
             y: slat,
-            z: slat * clon,
+            z: clat * clon,
         })
     }
 
+    /// Inverse of `from_lat_long`. Longitude is within -PI to PI.
     pub fn to_lat_long(&self) -> (Angle<F>, Angle<F>) {
-        let lat = self.0.y.acos();
-        let lon = (self.0.z / self.0.y).acos();
+        let lat = self.0.y.max(-F::one()).min(F::one()).asin();
+        let lon = self.0.x.atan2(self.0.z);
         (Angle::from_radians(lat), Angle::from_radians(lon))
     }
 }
+
+// ----------------------------------------------------------------------------
+// Display / FromStr (same format as the underlying vector)
+
+impl<F: FullFloat + fmt::Display> fmt::Display for Direction2<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<F: FullFloat + fmt::Display> fmt::Display for Direction3<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+/// Parses like a Vec2, then normalizes. Fails on a zero-length or
+/// non-finite vector.
+impl<F: FullFloat> FromStr for Direction2<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Direction2<F>, ParseError> {
+        let v: Vec2<F> = s.parse()?;
+        Direction2::try_from_vec(v).map_err(normalize_error)
+    }
+}
+
+/// Parses like a Vec3, then normalizes. Fails on a zero-length or
+/// non-finite vector.
+impl<F: FullFloat> FromStr for Direction3<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Direction3<F>, ParseError> {
+        let v: Vec3<F> = s.parse()?;
+        Direction3::try_from_vec(v).map_err(normalize_error)
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use float_cmp::ApproxEq;
+    use crate::{Angle, ParseError};
+    use super::{Direction2, Direction3};
+
+    #[test]
+    fn test_orthonormal_basis() {
+        use crate::Vec3;
+
+        let dirs = [Vec3::new(0.0_f64, 0.0, 1.0), Vec3::new(0.0, 0.0, -1.0),
+                    Vec3::new(1e-9, 0.0, -1.0), Vec3::new(1.0, 0.0, 0.0),
+                    Vec3::new(0.3, -0.4, 0.5), Vec3::new(-2.0, 7.0, -0.001)];
+        for v in dirs.iter() {
+            let n: Direction3<f64> = From::from(*v);
+            let (b1, b2) = n.orthonormal_basis();
+            assert!(((*b1).magnitude() - 1.0).abs() < 1e-12);
+            assert!(((*b2).magnitude() - 1.0).abs() < 1e-12);
+            assert!(b1.dot(b2).abs() < 1e-12);
+            assert!(b1.dot(n).abs() < 1e-12);
+            assert!(b2.dot(n).abs() < 1e-12);
+            assert!(b1.cross(b2).approx_eq(&*n, (1e-12, 8)));
+        }
+    }
+
+    #[test]
+    fn test_try_new() {
+        use crate::{Vec2, Vec3, MathError};
+
+        assert_eq!(Direction2::try_new(0.6_f64, 0.8), Ok(Direction2::new_isnormal(0.6, 0.8)));
+        assert_eq!(Direction2::try_new(1.0_f64, 1.0), Err(MathError::NotNormalized));
+        assert_eq!(Direction2::try_new(0.0_f64, 0.0), Err(MathError::NotNormalized));
+        assert_eq!(Direction2::try_new(f64::NAN, 1.0), Err(MathError::NonFinite));
+        assert_eq!(Direction3::try_new(0.0_f64, 0.0, 1.0), Ok(Direction3::new_isnormal(0.0, 0.0, 1.0)));
+        assert_eq!(Direction3::try_new(0.0_f64, 2.0, 0.0), Err(MathError::NotNormalized));
+        assert_eq!(Direction3::try_new(0.0_f64, f64::INFINITY, 0.0), Err(MathError::NonFinite));
+
+        assert_eq!(Direction2::try_from_vec(Vec2::new(0.0_f64, -3.0)),
+                   Ok(Direction2::new_isnormal(0.0, -1.0)));
+        assert_eq!(Direction2::try_from_vec(Vec2::new(0.0_f64, 0.0)), Err(MathError::ZeroLength));
+        assert_eq!(Direction2::try_from_vec(Vec2::new(-f64::INFINITY, 0.0)), Err(MathError::NonFinite));
+        assert_eq!(Direction3::try_from_vec(Vec3::new(0.0_f64, 0.0, 0.0)), Err(MathError::ZeroLength));
+        assert_eq!(Direction3::try_from_vec(Vec3::new(f64::NAN, 0.0, 1.0)), Err(MathError::NonFinite));
+
+        // the magnitude of this overflows, but its direction is fine
+        let big = Direction3::try_from_vec(Vec3::new(1e300_f64, 1e300, 0.0)).unwrap();
+        let half = 0.5_f64.sqrt();
+        assert!(big.approx_eq(&Direction3::new_isnormal(half, half, 0.0), (1e-12, 4)));
+        // and this one underflows
+        assert_eq!(Direction3::try_from_vec(Vec3::new(1e-300_f64, 0.0, 0.0)),
+                   Ok(Direction3::new_isnormal(1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn test_lat_long() {
+        let margin = (1e-12, 4);
+        let d = Direction3::<f64>::from_lat_long(Angle::from_degrees(0.0), Angle::from_degrees(90.0));
+        assert!(d.approx_eq(&Direction3::new_isnormal(1.0, 0.0, 0.0), margin));
+        let d = Direction3::<f64>::from_lat_long(Angle::from_degrees(90.0), Angle::from_degrees(0.0));
+        assert!(d.approx_eq(&Direction3::new_isnormal(0.0, 1.0, 0.0), margin));
+
+        for &(lat, lon) in [(30.0, 45.0), (-60.0, 170.0), (10.0, -120.0)].iter() {
+            let d = Direction3::<f64>::from_lat_long(Angle::from_degrees(lat), Angle::from_degrees(lon));
+            assert!((d.magnitude() - 1.0).abs() < 1e-12);
+            let (la, lo) = d.to_lat_long();
+            assert!((la.as_degrees() - lat).abs() < 1e-9);
+            assert!((lo.as_degrees() - lon).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn test_display_from_str() {
+        let d = Direction3::new_isnormal(0.0_f32, 1.0, 0.0);
+        assert_eq!(format!("{}", d), "(0, 1, 0)");
+        assert_eq!(format!("{}", d).parse::<Direction3<f32>>(), Ok(d));
+        assert_eq!("(0, 5, 0)".parse::<Direction3<f32>>(), Ok(d));
+        assert_eq!("3 4".parse::<Direction2<f64>>(),
+                   Ok(Direction2::new_isnormal(0.6, 0.8)));
+        assert_eq!("0 0 0".parse::<Direction3<f32>>(), Err(ParseError::ZeroLength));
+        assert_eq!("1e20 0 0".parse::<Direction3<f32>>(),
+                   Ok(Direction3::new_isnormal(1.0, 0.0, 0.0)));
+        assert_eq!("1e-30 0".parse::<Direction2<f32>>(), Ok(Direction2::new_isnormal(1.0, 0.0)));
+        assert_eq!("NaN 0 0".parse::<Direction3<f32>>(), Err(ParseError::NonFinite));
+        assert_eq!("0 -inf".parse::<Direction2<f64>>(), Err(ParseError::NonFinite));
+    }
+}
@@ -7,12 +7,19 @@ pub use self::direction::{Direction2, Direction3,
                           X_AXIS_F32, Y_AXIS_F32, Z_AXIS_F32,
                           X_AXIS_F64, Y_AXIS_F64, Z_AXIS_F64};
 
+#[macro_use]
+mod swizzle;
+
 use std::ops::{Index, IndexMut, Mul, MulAssign, Div, DivAssign, Neg,
-               Add, AddAssign, Sub, SubAssign};
+               Add, AddAssign, Sub, SubAssign, Rem};
 use std::default::Default;
+use std::fmt;
+use std::str::FromStr;
+use num_traits::NumCast;
 use serde::{Serialize, Deserialize};
 use float_cmp::ApproxEq;
 use crate::FullFloat;
+use crate::parse::{ParseError, parse_floats, write_list};
 
 /// A 2-element vector
 #[repr(C)]
@@ -189,6 +196,10 @@ impl<F: FullFloat> Vec4<F> {
 
 macro_rules! impl_vector {
     ($VecN:ident { $first:ident, $($field:ident),* }) => {
+        impl_swizzle!($VecN [$first $($field)*] => Vec2 [_ _]);
+        impl_swizzle!($VecN [$first $($field)*] => Vec3 [_ _ _]);
+        impl_swizzle!($VecN [$first $($field)*] => Vec4 [_ _ _ _]);
+
         impl<F: FullFloat> $VecN<F> {
             /// Construct a new vector
             #[inline]
@@ -372,6 +383,229 @@ macro_rules! impl_vector {
                 }
             }
         }
+
+        impl<F: FullFloat> Div<$VecN<F>> for $VecN<F> {
+            type Output = $VecN<F>;
+
+            #[inline]
+            fn div(self, rhs: $VecN<F>) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first / rhs.$first,
+                    $($field: self.$field / rhs.$field),*
+                }
+            }
+        }
+
+        impl<F: FullFloat> Rem<F> for $VecN<F> {
+            type Output = $VecN<F>;
+
+            #[inline]
+            fn rem(self, rhs: F) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first % rhs,
+                    $($field: self.$field % rhs),*
+                }
+            }
+        }
+
+        impl<F: FullFloat> Rem<$VecN<F>> for $VecN<F> {
+            type Output = $VecN<F>;
+
+            #[inline]
+            fn rem(self, rhs: $VecN<F>) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first % rhs.$first,
+                    $($field: self.$field % rhs.$field),*
+                }
+            }
+        }
+
+        // -- component-wise functions --
+
+        impl<F: FullFloat> $VecN<F>
+        {
+            /// Component-wise minimum
+            #[inline]
+            pub fn min(&self, rhs: $VecN<F>) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.min(rhs.$first),
+                    $($field: self.$field.min(rhs.$field)),*
+                }
+            }
+
+            /// Component-wise maximum
+            #[inline]
+            pub fn max(&self, rhs: $VecN<F>) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.max(rhs.$first),
+                    $($field: self.$field.max(rhs.$field)),*
+                }
+            }
+
+            /// Component-wise clamp between min and max
+            #[inline]
+            pub fn clamp(&self, min: $VecN<F>, max: $VecN<F>) -> $VecN<F> {
+                self.max(min).min(max)
+            }
+
+            #[inline]
+            pub fn abs(&self) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.abs(),
+                    $($field: self.$field.abs()),*
+                }
+            }
+
+            #[inline]
+            pub fn signum(&self) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.signum(),
+                    $($field: self.$field.signum()),*
+                }
+            }
+
+            #[inline]
+            pub fn floor(&self) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.floor(),
+                    $($field: self.$field.floor()),*
+                }
+            }
+
+            #[inline]
+            pub fn ceil(&self) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.ceil(),
+                    $($field: self.$field.ceil()),*
+                }
+            }
+
+            #[inline]
+            pub fn round(&self) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.round(),
+                    $($field: self.$field.round()),*
+                }
+            }
+
+            /// Fractional part, as in GLSL: `x - floor(x)` (always non-negative)
+            #[inline]
+            pub fn fract(&self) -> $VecN<F> {
+                *self - self.floor()
+            }
+
+            #[inline]
+            pub fn recip(&self) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.recip(),
+                    $($field: self.$field.recip()),*
+                }
+            }
+
+            #[inline]
+            pub fn sqrt(&self) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.sqrt(),
+                    $($field: self.$field.sqrt()),*
+                }
+            }
+
+            #[inline]
+            pub fn powf(&self, n: F) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.powf(n),
+                    $($field: self.$field.powf(n)),*
+                }
+            }
+
+            /// Linear interpolation from self (t=0) to other (t=1)
+            #[inline]
+            pub fn lerp(&self, other: $VecN<F>, t: F) -> $VecN<F> {
+                *self + (other - *self) * t
+            }
+
+            /// 0 where the component is less than edge, 1 otherwise (as in GLSL)
+            #[inline]
+            pub fn step(&self, edge: $VecN<F>) -> $VecN<F> {
+                $VecN {
+                    $first: if self.$first < edge.$first { F::zero() } else { F::one() },
+                    $($field: if self.$field < edge.$field { F::zero() } else { F::one() }),*
+                }
+            }
+
+            /// Hermite interpolation between 0 and 1 as the component moves
+            /// from edge0 to edge1 (as in GLSL)
+            #[inline]
+            pub fn smoothstep(&self, edge0: $VecN<F>, edge1: $VecN<F>) -> $VecN<F> {
+                let two: F = NumCast::from(2.0_f32).unwrap();
+                let three: F = NumCast::from(3.0_f32).unwrap();
+                let zero = $VecN { $first: F::zero(), $($field: F::zero()),* };
+                let one = $VecN { $first: F::one(), $($field: F::one()),* };
+                let t = ((*self - edge0) / (edge1 - edge0)).clamp(zero, one);
+                t * t * (one * three - t * two)
+            }
+
+            /// Computes `self * a + b` per component, with only one rounding error
+            #[inline]
+            pub fn mul_add(&self, a: $VecN<F>, b: $VecN<F>) -> $VecN<F> {
+                $VecN {
+                    $first: self.$first.mul_add(a.$first, b.$first),
+                    $($field: self.$field.mul_add(a.$field, b.$field)),*
+                }
+            }
+        }
+
+        // -- formatting and parsing --
+
+        /// Formats as "(x, y, z)", passing precision etc. on to each component
+        impl<F: FullFloat + fmt::Display> fmt::Display for $VecN<F> {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write_list(f, &[self.$first, $(self.$field),*])
+            }
+        }
+
+        /// Parses "(x, y, z)", "[x, y, z]" or just "x y z"
+        impl<F: FullFloat> FromStr for $VecN<F> {
+            type Err = ParseError;
+
+            fn from_str(s: &str) -> Result<$VecN<F>, ParseError> {
+                let n = [stringify!($first), $(stringify!($field)),*].len();
+                let mut v = parse_floats::<F>(s, n)?.into_iter();
+                Ok($VecN {
+                    $first: v.next().unwrap(),
+                    $($field: v.next().unwrap()),*
+                })
+            }
+        }
+
+        // -- reductions --
+
+        impl<F: FullFloat> $VecN<F>
+        {
+            /// The smallest component
+            #[inline]
+            pub fn min_element(&self) -> F {
+                self.$first $(.min(self.$field))*
+            }
+
+            /// The largest component
+            #[inline]
+            pub fn max_element(&self) -> F {
+                self.$first $(.max(self.$field))*
+            }
+
+            /// Sum of the components
+            #[inline]
+            pub fn sum(&self) -> F {
+                self.$first $(+ self.$field)*
+            }
+
+            /// Product of the components
+            #[inline]
+            pub fn product(&self) -> F {
+                self.$first $(* self.$field)*
+            }
+        }
     }
 }
 
@@ -418,7 +652,7 @@ impl<F: FullFloat> From<Vec3<F>> for Vec2<F> {
 
 impl<F: FullFloat> Vec3<F> {
     pub fn to_vec4(&self, w: F) -> Vec4<F> {
-        Vec4 { x: self.x, y: self.y, z: self.z, w: w }
+        Vec4 { x: self.x, y: self.y, z: self.z, w }
     }
 }
 
@@ -489,7 +723,7 @@ impl From<Vec4<f32>> for Vec4<f64> {
 // ----------------------------------------------------------------------------
 // Approx Eq
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Vec2<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Vec2<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -499,7 +733,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Vec2<
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Vec3<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Vec3<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -510,7 +744,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Vec3<
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Vec4<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Vec4<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -527,7 +761,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Vec4<
 #[cfg(test)]
 mod tests {
     use float_cmp::{ApproxEq, F32Margin};
-    use super::Vec2;
+    use super::{Vec2, Vec3, Vec4};
     const VEC2: Vec2<f32> = Vec2 { x: 1.0, y: 2.0 };
 
     #[test]
@@ -560,4 +794,77 @@ mod tests {
         v[1] = 6.0;
         assert_eq!(v.y, 6.0);
     }
+
+    #[test]
+    fn test_component_wise() {
+        let a: Vec3<f32> = Vec3::new(-1.5, 2.25, 4.0);
+        let b: Vec3<f32> = Vec3::new(1.0, -3.0, 4.5);
+        assert_eq!(a.min(b), Vec3::new(-1.5, -3.0, 4.0));
+        assert_eq!(a.max(b), Vec3::new(1.0, 2.25, 4.5));
+        assert_eq!(a.clamp(Vec3::new(-1.0, 0.0, 0.0), Vec3::new(1.0, 1.0, 1.0)),
+                   Vec3::new(-1.0, 1.0, 1.0));
+        assert_eq!(a.abs(), Vec3::new(1.5, 2.25, 4.0));
+        assert_eq!(a.signum(), Vec3::new(-1.0, 1.0, 1.0));
+        assert_eq!(a.floor(), Vec3::new(-2.0, 2.0, 4.0));
+        assert_eq!(a.ceil(), Vec3::new(-1.0, 3.0, 4.0));
+        assert_eq!(a.round(), Vec3::new(-2.0, 2.0, 4.0));
+        assert_eq!(a.fract(), Vec3::new(0.5, 0.25, 0.0));
+        assert_eq!(Vec3::new(2.0_f32, 4.0, 0.5).recip(), Vec3::new(0.5, 0.25, 2.0));
+        assert_eq!(Vec3::new(4.0_f32, 9.0, 16.0).sqrt(), Vec3::new(2.0, 3.0, 4.0));
+        assert_eq!(Vec3::new(2.0_f32, 3.0, 4.0).powf(2.0), Vec3::new(4.0, 9.0, 16.0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(Vec2::new(0.0_f32, 10.0).lerp(Vec2::new(10.0, 20.0), 0.25),
+                   Vec2::new(2.5, 12.5));
+        assert_eq!(a.step(Vec3::new(0.0, 0.0, 4.0)), Vec3::new(0.0, 1.0, 1.0));
+        assert_eq!(a.mul_add(b, Vec3::new(1.0, 1.0, 1.0)), Vec3::new(-0.5, -5.75, 19.0));
+    }
+
+    #[test]
+    fn test_smoothstep() {
+        let e0: Vec4<f64> = Vec4::new(0.0, 0.0, 0.0, 0.0);
+        let e1: Vec4<f64> = Vec4::new(1.0, 1.0, 1.0, 1.0);
+        let v: Vec4<f64> = Vec4::new(-1.0, 0.5, 2.0, 0.25);
+        assert_eq!(v.smoothstep(e0, e1), Vec4::new(0.0, 0.5, 1.0, 0.15625));
+    }
+
+    #[test]
+    fn test_reductions() {
+        let v: Vec4<f32> = Vec4::new(3.0, -1.0, 2.0, 4.0);
+        assert_eq!(v.min_element(), -1.0);
+        assert_eq!(v.max_element(), 4.0);
+        assert_eq!(v.sum(), 8.0);
+        assert_eq!(v.product(), -24.0);
+        let v: Vec2<f32> = Vec2::new(3.0, -1.0);
+        assert_eq!(v.min_element(), -1.0);
+        assert_eq!(v.product(), -3.0);
+    }
+
+    #[test]
+    fn test_display() {
+        let v: Vec3<f32> = Vec3::new(1.0, -2.5, 3.25);
+        assert_eq!(format!("{}", v), "(1, -2.5, 3.25)");
+        assert_eq!(format!("{:.2}", v), "(1.00, -2.50, 3.25)");
+        assert_eq!(format!("{}", VEC2), "(1, 2)");
+        assert_eq!(format!("{:.1}", Vec4::new(0.0_f64, 1.0, 2.0, 3.0)), "(0.0, 1.0, 2.0, 3.0)");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let v: Vec3<f32> = Vec3::new(1.0, -2.5, 3.25);
+        assert_eq!(format!("{}", v).parse::<Vec3<f32>>(), Ok(v));
+        assert_eq!("10 20 30".parse::<Vec3<f32>>(), Ok(Vec3::new(10.0, 20.0, 30.0)));
+        assert_eq!("[1,2]".parse::<Vec2<f64>>(), Ok(Vec2::new(1.0, 2.0)));
+        assert!("1 2".parse::<Vec4<f32>>().is_err());
+        assert!("1 2 three".parse::<Vec3<f32>>().is_err());
+    }
+
+    #[test]
+    fn test_div_rem() {
+        let a: Vec3<f32> = Vec3::new(7.0, -7.0, 9.0);
+        let b: Vec3<f32> = Vec3::new(2.0, 2.0, 4.0);
+        assert_eq!(a / b, Vec3::new(3.5, -3.5, 2.25));
+        assert_eq!(a % b, Vec3::new(1.0, -1.0, 1.0));
+        assert_eq!(a % 4.0, Vec3::new(3.0, -3.0, 1.0));
+    }
 }
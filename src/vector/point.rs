@@ -1,9 +1,12 @@
 
 use std::ops::{Deref, Sub, Add, Neg};
+use std::fmt;
+use std::str::FromStr;
 use serde::{Serialize, Deserialize};
 use float_cmp::ApproxEq;
 use super::{Vec2, Vec3, Vec4};
 use crate::FullFloat;
+use crate::parse::ParseError;
 
 /// Point vector in 2-dimensions
 #[repr(C)]
@@ -88,9 +91,10 @@ impl<F: FullFloat> From<Vec4<F>> for Point3<F> {
 // ----------------------------------------------------------------------------
 
 impl<F: FullFloat> Point3<F> {
-    #[allow(dead_code)]
+    /// Convert from homogeneous coordinates, dividing by w.  Returns None
+    /// for a point at infinity (w of zero).
     #[inline]
-    fn from_vec4(v: Vec4<F>) -> Option<Point3<F>> {
+    pub fn from_vec4(v: Vec4<F>) -> Option<Point3<F>> {
         if v.w == F::zero() { return None; }
         Some(Point3(Vec3::new(v.x/v.w, v.y/v.w, v.z/v.w)))
     }
@@ -190,10 +194,41 @@ impl From<Point3<f32>> for Point3<f64> {
     }
 }
 
+// ----------------------------------------------------------------------------
+// Display / FromStr (same format as the underlying vector)
+
+impl<F: FullFloat + fmt::Display> fmt::Display for Point2<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<F: FullFloat + fmt::Display> fmt::Display for Point3<F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<F: FullFloat> FromStr for Point2<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Point2<F>, ParseError> {
+        Ok(Point2(s.parse()?))
+    }
+}
+
+impl<F: FullFloat> FromStr for Point3<F> {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Point3<F>, ParseError> {
+        Ok(Point3(s.parse()?))
+    }
+}
+
 // ----------------------------------------------------------------------------
 // ApproxEq
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Point2<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Point2<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -202,7 +237,7 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Point
     }
 }
 
-impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Point3<F> {
+impl<M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &Point3<F> {
     type Margin = M;
 
     fn approx_eq<T: Into<Self::Margin>>(self, other: Self, margin: T) -> bool {
@@ -210,3 +245,20 @@ impl<'a, M: Copy + Default, F: Copy + ApproxEq<Margin=M>> ApproxEq for &'a Point
         self.0.approx_eq(&other.0, margin)
     }
 }
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::{Point2, Point3};
+
+    #[test]
+    fn test_display_from_str() {
+        let p = Point3::new(10.0_f32, 20.0, -30.5);
+        assert_eq!(format!("{}", p), "(10, 20, -30.5)");
+        assert_eq!(format!("{:.1}", Point2::new(1.0_f64, 2.0)), "(1.0, 2.0)");
+        assert_eq!("10 20 -30.5".parse::<Point3<f32>>(), Ok(p));
+        assert_eq!(format!("{}", p).parse::<Point3<f32>>(), Ok(p));
+        assert!("10 20".parse::<Point3<f32>>().is_err());
+    }
+}
@@ -0,0 +1,85 @@
+
+use super::{Point2, Point3};
+
+// Shader-style swizzles, e.g. v.zyx() or v.xyzz().  Every combination of the
+// source components is generated for each output size: `impl_vector!`
+// invokes this with each vector's own component list.  Points only swizzle
+// into other points of two or three dimensions; anything else is available
+// through the Deref to the underlying vector.
+//
+// impl_swizzle!(Src [components] => Dst [one `_` per output component])
+
+macro_rules! impl_swizzle {
+    ($Src:ident [$($c:ident)*] => $Dst:ident $slots:tt) => {
+        impl<F: crate::FullFloat> $Src<F> {
+            impl_swizzle!(@next $Dst [] $slots [$($c)*]);
+        }
+    };
+
+    // Every slot is filled: emit the accessor
+    (@next $Dst:ident [$($p:ident)*] [] $comps:tt) => {
+        paste::paste! {
+            #[inline]
+            pub fn [<$($p)*>](&self) -> $Dst<F> {
+                $Dst::new($(self.$p),*)
+            }
+        }
+    };
+    // Fill the next slot with each component in turn
+    (@next $Dst:ident $prefix:tt [_ $($slot:tt)*] $comps:tt) => {
+        impl_swizzle!(@each $Dst $prefix [$($slot)*] $comps $comps);
+    };
+    (@each $Dst:ident $prefix:tt $slots:tt [$($c:ident)*] $comps:tt) => {
+        $( impl_swizzle!(@push $Dst $prefix $c $slots $comps); )*
+    };
+    (@push $Dst:ident [$($p:ident)*] $c:ident $slots:tt $comps:tt) => {
+        impl_swizzle!(@next $Dst [$($p)* $c] $slots $comps);
+    };
+}
+
+impl_swizzle!(Point2 [x y] => Point2 [_ _]);
+impl_swizzle!(Point3 [x y z] => Point2 [_ _]);
+impl_swizzle!(Point3 [x y z] => Point3 [_ _ _]);
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec2, Vec3, Vec4, Point2, Point3};
+
+    #[test]
+    fn test_vec_swizzle() {
+        let v2 = Vec2::new(1.0_f32, 2.0);
+        assert_eq!(v2.yx(), Vec2::new(2.0, 1.0));
+        assert_eq!(v2.xyy(), Vec3::new(1.0, 2.0, 2.0));
+        assert_eq!(v2.yxyx(), Vec4::new(2.0, 1.0, 2.0, 1.0));
+
+        let v3 = Vec3::new(1.0_f32, 2.0, 3.0);
+        assert_eq!(v3.xz(), Vec2::new(1.0, 3.0));
+        assert_eq!(v3.zyx(), Vec3::new(3.0, 2.0, 1.0));
+        assert_eq!(v3.xzy(), Vec3::new(1.0, 3.0, 2.0));
+        assert_eq!(v3.xyzz(), Vec4::new(1.0, 2.0, 3.0, 3.0));
+        assert_eq!(v3.xyz(), v3);
+
+        let v4 = Vec4::new(1.0_f64, 2.0, 3.0, 4.0);
+        assert_eq!(v4.wz(), Vec2::new(4.0, 3.0));
+        assert_eq!(v4.xyw(), Vec3::new(1.0, 2.0, 4.0));
+        assert_eq!(v4.wzyx(), Vec4::new(4.0, 3.0, 2.0, 1.0));
+        assert_eq!(v4.xyzw(), v4);
+    }
+
+    #[test]
+    fn test_point_swizzle() {
+        let p2 = Point2::new(1.0_f32, 2.0);
+        assert_eq!(p2.yx(), Point2::new(2.0, 1.0));
+
+        let p3 = Point3::new(1.0_f32, 2.0, 3.0);
+        let flat: Point2<f32> = p3.xz();
+        assert_eq!(flat, Point2::new(1.0, 3.0));
+        let swapped: Point3<f32> = p3.xzy();
+        assert_eq!(swapped, Point3::new(1.0, 3.0, 2.0));
+
+        // wider swizzles still go through the underlying vector
+        assert_eq!(p3.xyzz(), Vec4::new(1.0, 2.0, 3.0, 3.0));
+    }
+}
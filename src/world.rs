@@ -0,0 +1,213 @@
+
+use std::ops::{Add, Sub};
+use serde::{Serialize, Deserialize};
+use crate::{Vec3, Vec4, Point3, Mat4, Position};
+
+/// A cubic region of the world, identified by integer coordinates.
+///
+/// Sector `(i, j, k)` covers `[i*size, (i+1)*size)` on each axis, for
+/// whatever sector size the caller uses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[derive(Serialize, Deserialize)]
+pub struct Sector {
+    pub x: i64,
+    pub y: i64,
+    pub z: i64,
+}
+
+impl Sector {
+    #[inline]
+    pub fn new(x: i64, y: i64, z: i64) -> Sector {
+        Sector { x, y, z }
+    }
+}
+
+/// A location in a world too large for f32 precision.
+///
+/// It is held as an f64 point.  Before handing geometry to the GPU, rebase it
+/// relative to a nearby origin (typically the camera) with `relative_to`,
+/// `rebase_transform` or `rebase_position`, which subtract in f64 and only
+/// then narrow to f32, so the result is precise near the origin no matter
+/// how far that origin is from the world's center.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Serialize, Deserialize)]
+pub struct WorldPosition(pub Point3<f64>);
+
+impl WorldPosition {
+    #[inline]
+    pub fn new(x: f64, y: f64, z: f64) -> WorldPosition {
+        WorldPosition(Point3::new(x, y, z))
+    }
+}
+
+// -- sectors -----------------------------------------------------------------
+
+impl WorldPosition {
+    /// Build from a sector and an offset from the sector's minimum corner
+    pub fn from_sector(sector: Sector, offset: Point3<f32>, sector_size: f64) -> WorldPosition {
+        let offset: Point3<f64> = From::from(offset);
+        WorldPosition::new(sector.x as f64 * sector_size + offset.x,
+                           sector.y as f64 * sector_size + offset.y,
+                           sector.z as f64 * sector_size + offset.z)
+    }
+}
+
+impl WorldPosition {
+    /// Split into the containing sector and the f32 offset within it
+    pub fn to_sector(&self, sector_size: f64) -> (Sector, Point3<f32>) {
+        let sx = (self.0.x / sector_size).floor();
+        let sy = (self.0.y / sector_size).floor();
+        let sz = (self.0.z / sector_size).floor();
+        let offset = Point3::new(self.0.x - sx * sector_size,
+                                 self.0.y - sy * sector_size,
+                                 self.0.z - sz * sector_size);
+        (Sector::new(sx as i64, sy as i64, sz as i64), From::from(offset))
+    }
+}
+
+// -- rebasing ----------------------------------------------------------------
+
+impl WorldPosition {
+    /// This position as an f32 point relative to `origin`
+    pub fn relative_to(&self, origin: &WorldPosition) -> Point3<f32> {
+        From::from(Point3(*self - *origin))
+    }
+}
+
+impl WorldPosition {
+    /// Rebase an f64 world transform (model to world) into an f32 transform
+    /// from model space into the space centered at `self`.
+    pub fn rebase_transform(&self, m: &Mat4<f64>) -> Mat4<f32> {
+        // Equivalent to translate(-self) * m, computed in f64
+        let o = self.0;
+        let shift = |c: Vec4<f64>| Vec4::new(c.x - o.x * c.w,
+                                             c.y - o.y * c.w,
+                                             c.z - o.z * c.w,
+                                             c.w);
+        let mut out = *m;
+        out.x = shift(m.x);
+        out.y = shift(m.y);
+        out.z = shift(m.z);
+        out.p = shift(m.p);
+        From::from(out)
+    }
+}
+
+impl WorldPosition {
+    /// Rebase an f64 position into f32, relative to `self`
+    pub fn rebase_position(&self, p: &Position<f64>) -> Position<f32> {
+        Position {
+            point: WorldPosition(p.point).relative_to(self),
+            ori: From::from(p.ori),
+        }
+    }
+}
+
+// ----------------------------------------------------------------------------
+// Operators
+
+impl Add<Vec3<f64>> for WorldPosition {
+    type Output = WorldPosition;
+
+    #[inline]
+    fn add(self, other: Vec3<f64>) -> WorldPosition {
+        WorldPosition(self.0 + other)
+    }
+}
+
+impl Sub<Vec3<f64>> for WorldPosition {
+    type Output = WorldPosition;
+
+    #[inline]
+    fn sub(self, other: Vec3<f64>) -> WorldPosition {
+        WorldPosition(self.0 - other)
+    }
+}
+
+impl Sub<WorldPosition> for WorldPosition {
+    type Output = Vec3<f64>;
+
+    #[inline]
+    fn sub(self, other: WorldPosition) -> Vec3<f64> {
+        self.0 - other.0
+    }
+}
+
+impl From<Point3<f64>> for WorldPosition {
+    fn from(p: Point3<f64>) -> WorldPosition {
+        WorldPosition(p)
+    }
+}
+
+impl From<WorldPosition> for Point3<f64> {
+    fn from(p: WorldPosition) -> Point3<f64> {
+        p.0
+    }
+}
+
+// ----------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use crate::{Vec3, Vec4, Point3, Mat4, NQuat, Position};
+    use super::{WorldPosition, Sector};
+
+    const FAR: f64 = 1.0e7;
+
+    #[test]
+    fn test_relative_precision() {
+        let camera = WorldPosition::new(FAR, -FAR, FAR);
+        let p = camera + Vec3::new(0.125, 0.001, -3.75);
+
+        // Narrowing first loses everything below a meter at this distance
+        let naive: Point3<f32> = From::from(p.0);
+        let naive_camera: Point3<f32> = From::from(camera.0);
+        assert!((naive - naive_camera).x != 0.125);
+
+        // Rebasing first keeps it
+        let rel = p.relative_to(&camera);
+        assert_eq!(rel.x, 0.125);
+        assert!((rel.y - 0.001).abs() < 1e-9);
+        assert_eq!(rel.z, -3.75);
+    }
+
+    #[test]
+    fn test_rebase_transform() {
+        let camera = WorldPosition::new(FAR, FAR, 0.0);
+        let mut m: Mat4<f64> = Mat4::scale(&Vec4::new(2.0, 2.0, 2.0, 1.0));
+        m.set_translation(Point3::new(FAR + 0.5, FAR - 0.25, 1.0));
+
+        let r = camera.rebase_transform(&m);
+        assert_eq!(r.get_translation(), Point3::new(0.5, -0.25, 1.0));
+
+        let model = Point3::new(0.0625_f32, 0.0, 0.0);
+        let model64: Point3<f64> = From::from(model);
+        let world: Vec4<f64> = m * Vec4::from(model64);
+        let rel: Vec4<f32> = r * Vec4::from(model);
+        let expected = WorldPosition(From::from(world)).relative_to(&camera);
+        assert_eq!(Point3::from(rel), expected);
+        assert_eq!(expected, Point3::new(0.625, -0.25, 1.0));
+    }
+
+    #[test]
+    fn test_rebase_position() {
+        let camera = WorldPosition::new(FAR, 0.0, FAR);
+        let p = Position {
+            point: Point3::new(FAR + 0.75, 2.0, FAR - 0.5),
+            ori: NQuat::identity(),
+        };
+        let r = camera.rebase_position(&p);
+        assert_eq!(r.point, Point3::new(0.75, 2.0, -0.5));
+        assert_eq!(r.ori, NQuat::identity());
+    }
+
+    #[test]
+    fn test_sectors() {
+        let size = 1024.0;
+        let p = WorldPosition::new(FAR + 0.125, -FAR - 0.5, 3.0);
+        let (sector, offset) = p.to_sector(size);
+        assert_eq!(sector, Sector::new(9765, -9766, 0));
+        assert!(offset.x >= 0.0 && offset.x < size as f32);
+        assert_eq!(WorldPosition::from_sector(sector, offset, size), p);
+    }
+}